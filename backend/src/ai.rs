@@ -1,27 +1,37 @@
 //! AI Strategy Module
 //! 
-//! Uses OpenRouter API with Gemini 3 Flash for real-time block selection.
+//! Uses an LLM provider (OpenRouter with Gemini 3 Flash by default, or any
+//! OpenAI-compatible endpoint) for real-time block selection. Without a
+//! provider, a trained local model (`model.rs`) is used if one is loaded.
 //! Achieves ultra-fast latency for sub-second decisions in final round seconds.
 
+use std::sync::Arc;
+
 use anyhow::{Result, Context};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, timeout_at, Instant};
 use tracing::{debug, info, warn};
+use uuid::Uuid;
 
+use crate::db::{Database, NewAiDecision};
+use crate::llm::LlmProvider;
+use crate::model::BlockModel;
 use crate::ore::BlockData;
 
-/// OpenRouter API endpoint
-const OPENROUTER_API_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
-
-/// Gemini 3 Flash Preview for ultra-fast mining decisions
-/// Fastest available model for real-time decisions
-const AI_MODEL: &str = "google/gemini-3-flash-preview";
+/// System prompt sent with every selection request
+const SYSTEM_PROMPT: &str = "You are an expert cryptocurrency mining strategist. Always respond with valid JSON only, no other text.";
 
 /// AI-based strategy selector
 #[derive(Clone)]
 pub struct AiStrategy {
-    client: Client,
-    api_key: String,
+    provider: Option<Arc<dyn LlmProvider>>,
+    /// Local model used instead of `fallback_selection` when there's no provider
+    local_model: Option<Arc<BlockModel>>,
+    /// Where every selection is recorded (`ai_decisions`)
+    audit: Option<Database>,
+    /// Experiment (id, arm) the selections are made for
+    experiment_arm: Option<(Uuid, String)>,
 }
 
 /// Block selection from AI
@@ -35,6 +45,202 @@ pub struct AiSelection {
     pub skip: bool,
     /// AI's reasoning
     pub reasoning: String,
+    /// Share of the deploy per selected block (sums to 1.0)
+    #[serde(default)]
+    pub weights: Vec<f64>,
+}
+
+/// Which answer a round's block selection came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionSource {
+    /// AI call made on the final snapshot
+    Fresh,
+    /// AI call started earlier in the round
+    Speculative,
+    /// Deterministic lowest-stake selection
+    Fallback,
+    /// Session's strategy script
+    Script,
+}
+
+impl SelectionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fresh => "fresh",
+            Self::Speculative => "speculative",
+            Self::Fallback => "fallback",
+            Self::Script => "script",
+        }
+    }
+}
+
+/// AI call running in the background for a grid snapshot
+pub struct PendingSelection {
+    handle: JoinHandle<(Result<AiSelection>, Instant)>,
+    started: Instant,
+    slots_remaining: u64,
+    ai: AiStrategy,
+    grid: GridState,
+    num_blocks: usize,
+    strategy_hint: &'static str,
+}
+
+impl PendingSelection {
+    /// Cancel the call
+    pub fn abort(self) {
+        self.handle.abort();
+    }
+    
+    /// Cancel a call cut off by the deadline, recording it with the answer used instead
+    ///
+    /// An aborted call never reaches its own audit write, so the row is written here.
+    fn time_out(self, used: SelectionSource) {
+        if self.handle.is_finished() {
+            // Finished after all; select_blocks already recorded it
+            return;
+        }
+        self.handle.abort();
+        
+        if self.ai.audit.is_none() {
+            return;
+        }
+        let elapsed = self.started.elapsed().as_millis() as i64;
+        let prompt = self.ai.provider.as_ref()
+            .map(|_| self.ai.build_prompt(&self.grid, self.num_blocks, self.strategy_hint));
+        let result = Err(anyhow::anyhow!("missed the decision deadline after {}ms", elapsed));
+        self.ai.record_decision(&self.grid, prompt, None, &result, elapsed, Some(used));
+    }
+}
+
+/// The selection used for a round, and how it was obtained
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedSelection {
+    pub selection: AiSelection,
+    pub source: SelectionSource,
+    /// Time the chosen AI call took (None for fallback)
+    pub latency_ms: Option<u64>,
+    /// Deadline minus the time the answer was ready; negative = late
+    pub margin_ms: i64,
+    /// Slots between the answer's snapshot and the final snapshot
+    pub snapshot_age_slots: u64,
+    /// Whether the fresh call was still running at the deadline
+    pub fresh_missed_deadline: bool,
+}
+
+impl ResolvedSelection {
+    /// A selection computed in-line (no AI call), timed against the deadline
+    pub fn immediate(selection: AiSelection, source: SelectionSource, started: Instant, deadline: Instant) -> Self {
+        let done = Instant::now();
+        Self {
+            selection,
+            source,
+            latency_ms: Some((done - started).as_millis() as u64),
+            margin_ms: signed_ms(done, deadline),
+            snapshot_age_slots: 0,
+            fresh_missed_deadline: false,
+        }
+    }
+}
+
+/// Signed milliseconds from `from` to `to`
+fn signed_ms(from: Instant, to: Instant) -> i64 {
+    if to >= from {
+        (to - from).as_millis() as i64
+    } else {
+        -((from - to).as_millis() as i64)
+    }
+}
+
+/// Equal weights for `n` blocks
+pub fn equal_weights(n: usize) -> Vec<f64> {
+    vec![1.0 / n.max(1) as f64; n]
+}
+
+/// Deterministic selection: the N lowest-stake blocks
+pub fn lowest_stake_selection(deployed: &[u64], num_blocks: usize) -> AiSelection {
+    let mut sorted: Vec<(usize, u64)> = deployed.iter().copied().enumerate().collect();
+    sorted.sort_by_key(|(_, stake)| *stake);
+    
+    let blocks: Vec<u8> = sorted.iter().take(num_blocks).map(|(i, _)| *i as u8).collect();
+    let min_stake = sorted.first().map(|(_, s)| *s).unwrap_or(0);
+    
+    AiSelection {
+        weights: equal_weights(blocks.len()),
+        blocks,
+        confidence: 0.9,
+        skip: false,
+        reasoning: format!("Lowest {} stake block(s), min {} lamports", num_blocks, min_stake),
+    }
+}
+
+/// Pick the best answer available at `deadline`
+///
+/// Order of preference: the fresh AI call (waited on until the deadline),
+/// then a finished speculative call, then the precomputed fallback.
+/// Unfinished calls are aborted and recorded as timed out.
+pub async fn resolve_selection(
+    fresh: Option<PendingSelection>,
+    speculative: Option<PendingSelection>,
+    fallback: AiSelection,
+    deadline: Instant,
+    slots_remaining: u64,
+) -> ResolvedSelection {
+    let resolved = |pending: &PendingSelection, selection, source, done: Instant| ResolvedSelection {
+        selection,
+        source,
+        latency_ms: Some((done - pending.started).as_millis() as u64),
+        margin_ms: signed_ms(done, deadline),
+        snapshot_age_slots: pending.slots_remaining.saturating_sub(slots_remaining),
+        fresh_missed_deadline: false,
+    };
+    
+    // Calls still running at the deadline, recorded once the answer is known
+    let mut late = Vec::new();
+    let mut fresh_missed_deadline = false;
+    if let Some(mut pending) = fresh {
+        match timeout_at(deadline, &mut pending.handle).await {
+            Ok(Ok((Ok(selection), done))) => {
+                return resolved(&pending, selection, SelectionSource::Fresh, done);
+            }
+            Ok(Ok((Err(e), _))) => warn!("Fresh AI selection failed: {}", e),
+            Ok(Err(e)) => warn!("Fresh AI selection task failed: {}", e),
+            Err(_) => {
+                fresh_missed_deadline = true;
+                warn!(
+                    "Fresh AI selection missed its deadline ({}ms in)",
+                    pending.started.elapsed().as_millis()
+                );
+                late.push(pending);
+            }
+        }
+    }
+    
+    let mut answer = None;
+    if let Some(mut pending) = speculative {
+        if pending.handle.is_finished() {
+            if let Ok((Ok(selection), done)) = (&mut pending.handle).await {
+                let mut speculative = resolved(&pending, selection, SelectionSource::Speculative, done);
+                speculative.fresh_missed_deadline = fresh_missed_deadline;
+                answer = Some(speculative);
+            }
+        } else {
+            late.push(pending);
+        }
+    }
+    
+    let answer = answer.unwrap_or_else(|| ResolvedSelection {
+        selection: fallback,
+        source: SelectionSource::Fallback,
+        latency_ms: None,
+        margin_ms: signed_ms(Instant::now(), deadline),
+        snapshot_age_slots: 0,
+        fresh_missed_deadline,
+    });
+    for pending in late {
+        pending.time_out(answer.source);
+    }
+    answer
 }
 
 /// Grid state for AI analysis
@@ -54,20 +260,187 @@ pub struct GridState {
     pub deploy_amount: u64,
     /// Tip cost
     pub tip_cost: u64,
+    /// Motherlode (ORE, raw units)
+    #[serde(default)]
+    pub motherlode: u64,
+    /// Lamports deployed per block since the previous snapshot (empty if none)
+    #[serde(default)]
+    pub recent_inflow: Vec<u64>,
 }
 
-impl AiStrategy {
-    /// Create a new AI strategy instance
-    pub fn new(api_key: String) -> Self {
-        Self {
-            client: Client::new(),
-            api_key,
+/// Strategy guidance included in the prompt
+fn strategy_guidance(strategy: &str) -> &'static str {
+    match strategy {
+        "conservative" => "Conservative: prefer squares with the least SOL and few miners. Skip unless a square offers a clear edge.",
+        "aggressive" => "Aggressive: always deploy. Favor low-stake squares, but busier squares are fine for a larger share of the pot.",
+        _ => "Best EV: maximize expected value (our pro-rata share of the other squares' SOL, minus deploy and tip). Skip only if every square is negative EV.",
+    }
+}
+
+/// JSON schema a selection reply must match
+pub fn selection_schema(num_blocks: usize) -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "required": ["blocks", "weights", "confidence", "skip", "reasoning"],
+        "properties": {
+            "blocks": {
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0, "maximum": 24 },
+                "maxItems": num_blocks,
+                "uniqueItems": true
+            },
+            "weights": {
+                "type": "array",
+                "items": { "type": "number", "minimum": 0 },
+                "maxItems": num_blocks
+            },
+            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+            "skip": { "type": "boolean" },
+            "reasoning": { "type": "string" }
+        },
+        // Skips send empty arrays; anything else names every square
+        "if": { "properties": { "skip": { "const": false } } },
+        "then": {
+            "properties": {
+                "blocks": { "minItems": num_blocks },
+                "weights": { "minItems": num_blocks }
+            }
+        }
+    })
+}
+
+/// Validate a parsed reply against `selection_schema`
+///
+/// A non-skip reply must name exactly `num_blocks` distinct squares with one
+/// non-negative weight each; weights are normalized to sum to 1.
+pub fn validate_selection(value: &serde_json::Value, num_blocks: usize) -> Result<AiSelection> {
+    let obj = value.as_object().context("reply is not a JSON object")?;
+    
+    let skip = obj.get("skip").and_then(|v| v.as_bool()).context("\"skip\" must be a boolean")?;
+    let confidence = obj.get("confidence").and_then(|v| v.as_f64()).context("\"confidence\" must be a number")?;
+    if !(0.0..=1.0).contains(&confidence) {
+        anyhow::bail!("\"confidence\" must be between 0 and 1, got {}", confidence);
+    }
+    let reasoning = obj.get("reasoning").and_then(|v| v.as_str()).context("\"reasoning\" must be a string")?;
+    
+    if skip {
+        return Ok(AiSelection {
+            blocks: vec![],
+            confidence,
+            skip: true,
+            reasoning: reasoning.to_string(),
+            weights: vec![],
+        });
+    }
+    
+    let raw_blocks = obj.get("blocks").and_then(|v| v.as_array()).context("\"blocks\" must be an array")?;
+    let mut blocks = Vec::with_capacity(raw_blocks.len());
+    for v in raw_blocks {
+        let index = v.as_u64().with_context(|| format!("block {} is not a non-negative integer", v))?;
+        if index > 24 {
+            anyhow::bail!("block {} is out of range 0-24", index);
         }
+        if blocks.contains(&(index as u8)) {
+            anyhow::bail!("block {} is listed twice", index);
+        }
+        blocks.push(index as u8);
+    }
+    if blocks.len() != num_blocks {
+        anyhow::bail!("expected {} block(s), got {}", num_blocks, blocks.len());
+    }
+    
+    let raw_weights = obj.get("weights").and_then(|v| v.as_array()).context("\"weights\" must be an array")?;
+    if raw_weights.len() != blocks.len() {
+        anyhow::bail!("expected {} weight(s), got {}", blocks.len(), raw_weights.len());
+    }
+    let weights = raw_weights.iter()
+        .map(|v| v.as_f64().filter(|w| w.is_finite() && *w >= 0.0))
+        .collect::<Option<Vec<f64>>>()
+        .context("weights must be non-negative numbers")?;
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        anyhow::bail!("weights must not all be zero");
+    }
+    
+    Ok(AiSelection {
+        blocks,
+        confidence,
+        skip: false,
+        reasoning: reasoning.to_string(),
+        weights: weights.iter().map(|w| w / total).collect(),
+    })
+}
+
+impl AiStrategy {
+    /// Create a new AI strategy instance (no provider = fallback mode)
+    pub fn new(provider: Option<Arc<dyn LlmProvider>>) -> Self {
+        Self { provider, local_model: None, audit: None, experiment_arm: None }
+    }
+    
+    /// Use a trained local model when no provider is configured
+    pub fn with_model(mut self, model: Arc<BlockModel>) -> Self {
+        self.local_model = Some(model);
+        self
+    }
+    
+    /// Record every selection in the `ai_decisions` audit log
+    pub fn with_audit(mut self, db: Database) -> Self {
+        self.audit = Some(db);
+        self
+    }
+    
+    /// Same strategy (audit log, local model) with a different provider
+    pub fn with_provider(&self, provider: Arc<dyn LlmProvider>) -> AiStrategy {
+        Self { provider: Some(provider), ..self.clone() }
+    }
+    
+    /// Same strategy with its decisions tagged with an experiment arm
+    pub fn with_experiment_arm(&self, experiment_id: Uuid, arm: &str) -> AiStrategy {
+        Self { experiment_arm: Some((experiment_id, arm.to_string())), ..self.clone() }
+    }
+    
+    /// Same strategy restricted to the local model (None if none is loaded)
+    pub fn local_only(&self) -> Option<AiStrategy> {
+        self.local_model.as_ref().map(|_| Self { provider: None, ..self.clone() })
     }
     
-    /// Check if AI is configured (has API key)
+    /// Check if AI is configured (has a provider or a local model)
     pub fn is_configured(&self) -> bool {
-        !self.api_key.is_empty()
+        self.provider.is_some() || self.local_model.is_some()
+    }
+    
+    /// Model used for selections ("fallback" without a provider or local model)
+    pub fn model(&self) -> String {
+        match (&self.provider, &self.local_model) {
+            (Some(p), _) => format!("{}/{}", p.name(), p.config().model),
+            (None, Some(model)) => format!("local/logistic-v{}", model.version),
+            (None, None) => "fallback".to_string(),
+        }
+    }
+    
+    /// Start a selection in the background for a grid snapshot
+    pub fn spawn_selection(
+        &self,
+        grid: GridState,
+        num_blocks: usize,
+        strategy_hint: &'static str,
+    ) -> PendingSelection {
+        let ai = self.clone();
+        let slots_remaining = grid.slots_remaining;
+        let snapshot = grid.clone();
+        let handle = tokio::spawn(async move {
+            let result = ai.select_blocks(&snapshot, num_blocks, strategy_hint).await;
+            (result, Instant::now())
+        });
+        PendingSelection {
+            handle,
+            started: Instant::now(),
+            slots_remaining,
+            ai: self.clone(),
+            grid,
+            num_blocks,
+            strategy_hint,
+        }
     }
     
     /// Get AI block selection based on current grid state
@@ -77,148 +450,190 @@ impl AiStrategy {
         num_blocks: usize,
         strategy_hint: &str, // "aggressive", "conservative", "best_ev"
     ) -> Result<AiSelection> {
-        if !self.is_configured() {
-            // Fallback to basic EV calculation if no API key
-            return self.fallback_selection(grid, num_blocks);
-        }
+        let started = Instant::now();
+        let (prompt, response, result) = self.run_selection(grid, num_blocks, strategy_hint).await;
+        
+        self.record_decision(grid, prompt, response, &result, started.elapsed().as_millis() as i64, None);
+        
+        result
+    }
+    
+    /// Selection plus the prompt and raw response that produced it
+    async fn run_selection(
+        &self,
+        grid: &GridState,
+        num_blocks: usize,
+        strategy_hint: &str,
+    ) -> (Option<String>, Option<String>, Result<AiSelection>) {
+        let Some(provider) = &self.provider else {
+            // Local model if trained, else basic EV calculation
+            let result = match &self.local_model {
+                Some(model) => Ok(model.select(grid, num_blocks)),
+                None => self.fallback_selection(grid, num_blocks),
+            };
+            return (None, None, result);
+        };
         
         let prompt = self.build_prompt(grid, num_blocks, strategy_hint);
         
-        let response = self.call_openrouter(&prompt).await?;
+        let response = match Self::call_provider(provider.as_ref(), &prompt).await {
+            Ok(response) => response,
+            Err(e) => return (Some(prompt), None, Err(e)),
+        };
+        
+        let error = match self.parse_response(&response, num_blocks) {
+            Ok(selection) => return (Some(prompt), Some(response), Ok(selection)),
+            Err(e) => e,
+        };
         
-        // Parse AI response
-        self.parse_response(&response, num_blocks)
+        // One repair attempt, quoting the invalid reply and what was wrong with it
+        warn!("Invalid AI response ({:#}), retrying with repair prompt", error);
+        let repair = Self::build_repair_prompt(&prompt, &response, &error);
+        let audit_prompt = format!("{}\n\n--- repair ---\n{}", prompt, repair);
+        match Self::call_provider(provider.as_ref(), &repair).await {
+            Ok(repaired) => {
+                let result = self.parse_response(&repaired, num_blocks);
+                (Some(audit_prompt), Some(format!("{}\n\n--- repair ---\n{}", response, repaired)), result)
+            }
+            Err(e) => (Some(audit_prompt), Some(response), Err(e)),
+        }
     }
     
-    /// Build concise prompt for fast AI response (~750ms target)
-    /// Strategy: ALWAYS pick the lowest stake block, never skip
-    fn build_prompt(&self, grid: &GridState, _num_blocks: usize, _strategy: &str) -> String {
-        // Build sorted list: (index, stake_sol)
-        let mut blocks_sorted: Vec<(usize, f64)> = grid.deployed.iter()
-            .enumerate()
-            .map(|(i, &d)| (i, d as f64 / 1_000_000_000.0))
-            .collect();
-        blocks_sorted.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    /// Write an `ai_decisions` row in the background (if auditing)
+    ///
+    /// `timed_out_for` is the answer used instead when the call was cut off by the deadline.
+    fn record_decision(
+        &self,
+        grid: &GridState,
+        prompt: Option<String>,
+        raw_response: Option<String>,
+        result: &Result<AiSelection>,
+        latency_ms: i64,
+        timed_out_for: Option<SelectionSource>,
+    ) {
+        let Some(db) = &self.audit else { return };
+        let (selection, error) = match result {
+            Ok(selection) => (Some(selection), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+        let decision = NewAiDecision {
+            model: self.model(),
+            round_id: grid.round_id as i64,
+            grid: serde_json::to_value(grid).unwrap_or_default(),
+            prompt,
+            raw_response,
+            selection: selection.and_then(|s| serde_json::to_value(s).ok()),
+            selected_blocks: selection
+                .filter(|s| !s.skip)
+                .map(|s| s.blocks.iter().map(|&b| b as i16).collect())
+                .unwrap_or_default(),
+            skip: selection.is_some_and(|s| s.skip),
+            error,
+            latency_ms,
+            timed_out: timed_out_for.is_some(),
+            used_instead: timed_out_for.map(|s| s.as_str().to_string()),
+            experiment_id: self.experiment_arm.as_ref().map(|(id, _)| *id),
+            experiment_arm: self.experiment_arm.as_ref().map(|(_, arm)| arm.clone()),
+        };
         
-        // Format block list: "idx:stake"
-        let blocks_str: String = blocks_sorted.iter()
-            .take(10) // Show lowest 10
-            .map(|(i, s)| format!("{}:{:.3}", i, s))
-            .collect::<Vec<_>>()
-            .join(",");
+        let db = db.clone();
+        tokio::spawn(async move {
+            if let Err(e) = db.record_ai_decision(&decision).await {
+                warn!("Failed to record AI decision: {}", e);
+            }
+        });
+    }
+    
+    /// Build the selection prompt: full grid, pot, motherlode, strategy and reply schema
+    fn build_prompt(&self, grid: &GridState, num_blocks: usize, strategy: &str) -> String {
+        let sol = |lamports: u64| lamports as f64 / 1_000_000_000.0;
         
-        let lowest_block = blocks_sorted.first().map(|(i, _)| *i).unwrap_or(0);
+        // 5x5 grid, one row per line: "idx: SOL/miners"
+        let rows: String = (0..5)
+            .map(|row| {
+                (0..5)
+                    .map(|col| {
+                        let i = row * 5 + col;
+                        let deployed = grid.deployed.get(i).copied().unwrap_or(0);
+                        let miners = grid.miner_counts.get(i).copied().unwrap_or(0);
+                        format!("{:>2}: {:.4}/{}", i, sol(deployed), miners)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
         
-        // Simple prompt - always pick lowest stake
         format!(
-            r#"ORE mining: Pick the LOWEST stake block.
-Blocks[idx:SOL] sorted by stake: [{}]
+            r#"ORE mining, round {round_id}: {slots} slots (~{secs:.1}s) left.
+One of the 25 squares wins at random. SOL on the other 24 squares is split pro-rata among the winning square's miners.
+Pot: {pot:.4} SOL. Motherlode: {motherlode:.2} ORE.
+We deploy {deploy:.4} SOL per square and pay a {tip:.4} SOL tip.
+
+Squares (index: SOL deployed/miners):
+{rows}
 
-ALWAYS pick a block. Never skip. Lowest is block {}.
-Reply JSON only: {{"blocks":[{}],"confidence":0.95,"skip":false,"reasoning":"lowest stake"}}"#,
-            blocks_str,
-            lowest_block,
-            lowest_block
+Strategy: {guidance}
+Pick exactly {n} distinct square(s) (0-24) with one weight per square for splitting our deploy.
+If no square is worth it, set "skip": true with empty "blocks" and "weights".
+
+Reply with JSON only, matching this schema:
+{schema}"#,
+            round_id = grid.round_id,
+            slots = grid.slots_remaining,
+            secs = grid.slots_remaining as f64 * 0.4,
+            pot = sol(grid.total_pot),
+            motherlode = grid.motherlode as f64 / 100_000_000_000.0,
+            deploy = sol(grid.deploy_amount),
+            tip = sol(grid.tip_cost),
+            rows = rows,
+            guidance = strategy_guidance(strategy),
+            n = num_blocks,
+            schema = selection_schema(num_blocks),
         )
     }
     
-    /// Call OpenRouter API
-    async fn call_openrouter(&self, prompt: &str) -> Result<String> {
-        let request_body = serde_json::json!({
-            "model": AI_MODEL,
-            "messages": [
-                {
-                    "role": "system",
-                    "content": "You are an expert cryptocurrency mining strategist. Always respond with valid JSON only, no other text."
-                },
-                {
-                    "role": "user", 
-                    "content": prompt
-                }
-            ],
-            "max_tokens": 300,
-            "temperature": 0.3
-        });
-        
-        let response = self.client
-            .post(OPENROUTER_API_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .header("HTTP-Referer", "https://orevault.app")
-            .header("X-Title", "OreVault Miner")
-            .json(&request_body)
-            .send()
-            .await
-            .context("Failed to call OpenRouter API")?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("OpenRouter API error {}: {}", status, body);
-        }
-        
-        let json: serde_json::Value = response.json().await
-            .context("Failed to parse OpenRouter response")?;
-        
-        let content = json["choices"][0]["message"]["content"]
-            .as_str()
-            .context("No content in OpenRouter response")?;
+    /// Prompt asking the model to fix an invalid reply
+    fn build_repair_prompt(prompt: &str, response: &str, error: &anyhow::Error) -> String {
+        format!(
+            "{}\n\nYour previous reply was rejected: {:#}\nPrevious reply:\n{}\n\nReply again with corrected JSON only.",
+            prompt, error, response
+        )
+    }
+    
+    /// Call the LLM provider within its configured timeout
+    async fn call_provider(provider: &dyn LlmProvider, prompt: &str) -> Result<String> {
+        let budget = provider.config().timeout;
+        let content = timeout(budget, provider.complete(SYSTEM_PROMPT, prompt)).await
+            .with_context(|| format!("{} timed out after {}ms", provider.name(), budget.as_millis()))??;
         
         debug!("AI response: {}", content);
         
-        Ok(content.to_string())
+        Ok(content)
     }
     
-    /// Parse AI response into selection
+    /// Parse AI response into a validated selection
     fn parse_response(&self, response: &str, num_blocks: usize) -> Result<AiSelection> {
-        // Try to extract JSON from response
-        let json_str = if response.contains("{") {
-            let start = response.find('{').unwrap();
-            let end = response.rfind('}').unwrap() + 1;
-            &response[start..end]
-        } else {
-            response
+        // Try to extract JSON from response (models sometimes wrap it in prose or fences)
+        let json_str = match (response.find('{'), response.rfind('}')) {
+            (Some(start), Some(end)) if end > start => &response[start..=end],
+            _ => response,
         };
         
         let parsed: serde_json::Value = serde_json::from_str(json_str)
             .context("Failed to parse AI JSON response")?;
+        let selection = validate_selection(&parsed, num_blocks)?;
         
-        let blocks: Vec<u8> = parsed["blocks"]
-            .as_array()
-            .context("No blocks array in response")?
-            .iter()
-            .filter_map(|v| v.as_u64().map(|n| n as u8))
-            .filter(|&b| b < 25)
-            .take(num_blocks)
-            .collect();
-        
-        let confidence = parsed["confidence"].as_f64().unwrap_or(0.5);
-        let skip = parsed["skip"].as_bool().unwrap_or(false);
-        let reasoning = parsed["reasoning"].as_str().unwrap_or("").to_string();
-        
-        // If AI says skip, return with skip=true
-        if skip {
-            info!("AI recommends SKIP: {}", reasoning);
-            return Ok(AiSelection {
-                blocks: vec![],
-                confidence,
-                skip: true,
-                reasoning,
-            });
-        }
-        
-        if blocks.is_empty() {
-            anyhow::bail!("AI returned no valid blocks");
+        if selection.skip {
+            info!("AI recommends SKIP: {}", selection.reasoning);
+        } else {
+            info!(
+                "AI selected blocks {:?} (weights {:?}) with confidence {:.2}",
+                selection.blocks, selection.weights, selection.confidence
+            );
         }
         
-        info!("AI selected blocks {:?} with confidence {:.2}", blocks, confidence);
-        
-        Ok(AiSelection {
-            blocks,
-            confidence,
-            skip: false,
-            reasoning,
-        })
+        Ok(selection)
     }
     
     /// Fallback selection using basic EV calculation (no AI)
@@ -246,6 +661,7 @@ Reply JSON only: {{"blocks":[{}],"confidence":0.95,"skip":false,"reasoning":"low
                 confidence: 1.0,
                 skip: true,
                 reasoning: "All blocks have equal stake - skipping".to_string(),
+                weights: vec![],
             });
         }
         
@@ -256,6 +672,7 @@ Reply JSON only: {{"blocks":[{}],"confidence":0.95,"skip":false,"reasoning":"low
                 confidence: 0.8,
                 skip: true,
                 reasoning: "No blocks below average stake".to_string(),
+                weights: vec![],
             });
         }
         
@@ -269,6 +686,7 @@ Reply JSON only: {{"blocks":[{}],"confidence":0.95,"skip":false,"reasoning":"low
         let lowest_stake = blocks_below_avg[0].1 as f64 / 1_000_000_000.0;
         
         Ok(AiSelection {
+            weights: equal_weights(blocks.len()),
             blocks,
             confidence: 0.8,
             skip: false,
@@ -282,7 +700,7 @@ mod tests {
     
     #[test]
     fn test_fallback_selection() {
-        let ai = AiStrategy::new(String::new()); // No API key = fallback mode
+        let ai = AiStrategy::new(None); // No provider = fallback mode
         
         let grid = GridState {
             deployed: vec![
@@ -298,6 +716,8 @@ mod tests {
             slots_remaining: 50,
             deploy_amount: 100_000_000, // 0.1 SOL
             tip_cost: 1_000_000, // 0.001 SOL
+            motherlode: 0,
+            recent_inflow: vec![],
         };
         
         let result = ai.fallback_selection(&grid, 3).unwrap();
@@ -307,4 +727,89 @@ mod tests {
         println!("Selected blocks: {:?}", result.blocks);
         println!("Reasoning: {}", result.reasoning);
     }
+    
+    #[tokio::test]
+    async fn test_resolve_prefers_fresh_then_speculative_then_fallback() {
+        use crate::llm::ScriptedProvider;
+        use tokio::time::Duration;
+        
+        let answer = |block: u8| Ok(format!(r#"{{"blocks":[{}],"weights":[1],"confidence":0.9,"skip":false,"reasoning":""}}"#, block));
+        let grid = |slots| GridState {
+            deployed: vec![0; 25],
+            miner_counts: vec![0; 25],
+            total_pot: 0,
+            round_id: 1,
+            slots_remaining: slots,
+            deploy_amount: 1,
+            tip_cost: 0,
+            motherlode: 0,
+            recent_inflow: vec![],
+        };
+        let fallback = lowest_stake_selection(&[5, 1, 3], 1);
+        assert_eq!(fallback.blocks, vec![1]);
+        
+        let ai = AiStrategy::new(Some(Arc::new(ScriptedProvider::new(vec![answer(3), answer(4)]))));
+        let speculative = ai.spawn_selection(grid(25), 1, "best_ev");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let fresh = ai.spawn_selection(grid(10), 1, "best_ev");
+        let deadline = Instant::now() + Duration::from_millis(500);
+        let resolved = resolve_selection(Some(fresh), Some(speculative), fallback.clone(), deadline, 10).await;
+        assert_eq!(resolved.source, SelectionSource::Fresh);
+        assert_eq!(resolved.selection.blocks, vec![4]);
+        
+        // Fresh call fails: the finished speculative answer wins
+        let ai = AiStrategy::new(Some(Arc::new(ScriptedProvider::new(vec![answer(3), Err("down".into())]))));
+        let speculative = ai.spawn_selection(grid(25), 1, "best_ev");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let fresh = ai.spawn_selection(grid(10), 1, "best_ev");
+        let resolved = resolve_selection(Some(fresh), Some(speculative), fallback.clone(), deadline, 10).await;
+        assert_eq!(resolved.source, SelectionSource::Speculative);
+        assert_eq!(resolved.snapshot_age_slots, 15);
+        
+        let resolved = resolve_selection(None, None, fallback, Instant::now(), 10).await;
+        assert_eq!(resolved.source, SelectionSource::Fallback);
+    }
+    
+    #[tokio::test]
+    async fn test_select_blocks_with_scripted_provider() {
+        use crate::llm::ScriptedProvider;
+        
+        let provider = ScriptedProvider::new(vec![
+            Ok(r#"{"blocks":[7,7],"weights":[1,1],"confidence":0.9,"skip":false,"reasoning":"lowest"}"#.to_string()),
+            Ok(r#"Fixed: {"blocks":[7,3],"weights":[3,1],"confidence":0.9,"skip":false,"reasoning":"lowest"}"#.to_string()),
+            Err("connection refused".to_string()),
+        ]);
+        let ai = AiStrategy::new(Some(Arc::new(provider)));
+        let grid = GridState {
+            deployed: vec![1_000_000; 25],
+            miner_counts: vec![1; 25],
+            total_pot: 25_000_000,
+            round_id: 1,
+            slots_remaining: 5,
+            deploy_amount: 100_000,
+            tip_cost: 1_000,
+            motherlode: 0,
+            recent_inflow: vec![],
+        };
+        
+        // Duplicate block is rejected, the repair reply is accepted
+        let selection = ai.select_blocks(&grid, 2, "best_ev").await.unwrap();
+        assert_eq!(selection.blocks, vec![7, 3]);
+        assert_eq!(selection.weights, vec![0.75, 0.25]);
+        assert!(ai.select_blocks(&grid, 2, "best_ev").await.is_err());
+    }
+    
+    #[test]
+    fn test_validate_selection() {
+        let check = |json: &str, n| validate_selection(&serde_json::from_str(json).unwrap(), n);
+        
+        assert!(check(r#"{"blocks":[1,2],"weights":[1,1],"confidence":0.5,"skip":false,"reasoning":""}"#, 2).is_ok());
+        assert!(check(r#"{"blocks":[],"weights":[],"confidence":0.5,"skip":true,"reasoning":"flat"}"#, 2).unwrap().skip);
+        // Duplicate, out of range, wrong count, missing weights, bad confidence
+        assert!(check(r#"{"blocks":[1,1],"weights":[1,1],"confidence":0.5,"skip":false,"reasoning":""}"#, 2).is_err());
+        assert!(check(r#"{"blocks":[1,25],"weights":[1,1],"confidence":0.5,"skip":false,"reasoning":""}"#, 2).is_err());
+        assert!(check(r#"{"blocks":[1],"weights":[1],"confidence":0.5,"skip":false,"reasoning":""}"#, 2).is_err());
+        assert!(check(r#"{"blocks":[1,2],"confidence":0.5,"skip":false,"reasoning":""}"#, 2).is_err());
+        assert!(check(r#"{"blocks":[1,2],"weights":[1,1],"confidence":1.5,"skip":false,"reasoning":""}"#, 2).is_err());
+    }
 }
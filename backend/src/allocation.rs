@@ -0,0 +1,284 @@
+//! Square Allocation
+//!
+//! Per-square deploy amounts. An ORE deploy instruction carries one amount
+//! for all of its squares, so an allocation is split into groups of squares
+//! sharing an amount (one instruction each), and the instructions are packed
+//! into as few transactions as fit the packet size and compute budget.
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+
+/// Smallest amount the program accepts per square (0.0001 SOL)
+pub const MIN_PER_SQUARE: u64 = 100_000;
+/// Compute units budgeted per deploy instruction
+pub const COMPUTE_UNITS_PER_DEPLOY: u32 = 200_000;
+/// Deploy instructions that fit one transaction's compute budget
+const MAX_DEPLOYS_PER_TX: usize = (1_400_000 / COMPUTE_UNITS_PER_DEPLOY) as usize;
+/// Thin allocation puts at most this multiple of the base amount on a square
+const MAX_SKEW: u64 = 4;
+
+/// Compute units for a transaction with `deploys` deploy instructions
+pub fn compute_units(deploys: usize) -> u32 {
+    (deploys as u32 * COMPUTE_UNITS_PER_DEPLOY).clamp(500_000, 1_400_000)
+}
+
+/// How a session spreads its stake over the selected squares
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationMode {
+    /// The same amount on every square
+    #[default]
+    Uniform,
+    /// More on thin squares, less on crowded ones
+    Thin,
+}
+
+/// Squares deployed with one instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeployGroup {
+    /// Lamports per square
+    pub amount: u64,
+    pub squares: [bool; 25],
+}
+
+impl DeployGroup {
+    /// Square indices in the group
+    pub fn indices(&self) -> Vec<u8> {
+        (0..25u8).filter(|&i| self.squares[i as usize]).collect()
+    }
+
+    /// Lamports across the group
+    pub fn total(&self) -> u64 {
+        self.amount.saturating_mul(self.indices().len() as u64)
+    }
+}
+
+/// Lamports per square (0 = not deployed)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocation {
+    amounts: [u64; 25],
+}
+
+impl Allocation {
+    /// `amount` on each of `squares`
+    pub fn uniform(squares: &[u8], amount: u64) -> Self {
+        let amounts: Vec<u64> = squares.iter().map(|_| amount).collect();
+        Self::from_squares(squares, &amounts)
+    }
+
+    /// `amounts[i]` on `squares[i]`; out-of-range squares are ignored
+    pub fn from_squares(squares: &[u8], amounts: &[u64]) -> Self {
+        let mut out = [0u64; 25];
+        for (&square, &amount) in squares.iter().zip(amounts) {
+            if square < 25 {
+                out[square as usize] = amount;
+            }
+        }
+        Self { amounts: out }
+    }
+
+    /// Spread `base_amount * squares` over `squares` in inverse proportion to
+    /// their current stake (see `weighted`)
+    pub fn thin(deployed: &[u64], squares: &[u8], base_amount: u64) -> Self {
+        let weights: Vec<f64> = squares.iter()
+            .map(|&s| 1.0 / (deployed.get(s as usize).copied().unwrap_or(0) + base_amount.max(1)) as f64)
+            .collect();
+        Self::weighted(squares, &weights, base_amount)
+    }
+
+    /// Spread `base_amount * squares` over `squares` by `weights` (any scale).
+    /// Amounts are rounded to a quarter of the base so similar squares share
+    /// an instruction, and kept within [one step, `MAX_SKEW` x base] without
+    /// the total going over `base_amount * squares`. Falls back to uniform
+    /// when the weights don't match the squares.
+    pub fn weighted(squares: &[u8], weights: &[f64], base_amount: u64) -> Self {
+        let sum: f64 = weights.iter().filter(|w| w.is_finite() && **w > 0.0).sum();
+        if weights.len() != squares.len() || sum <= 0.0 {
+            return Self::uniform(squares, base_amount);
+        }
+        let step = (base_amount / 4).max(MIN_PER_SQUARE);
+        let budget = base_amount.saturating_mul(squares.len() as u64);
+        let mut amounts: Vec<u64> = weights.iter()
+            .map(|w| {
+                let raw = budget as f64 * w.max(0.0) / sum;
+                let rounded = (raw / step as f64).round() as u64 * step;
+                rounded.clamp(step, base_amount.saturating_mul(MAX_SKEW).max(step))
+            })
+            .collect();
+        // Rounding up and the one-step floor can overshoot: take it off the largest amounts
+        while amounts.iter().sum::<u64>() > budget {
+            let Some(largest) = amounts.iter_mut().filter(|a| **a > step).max() else { break };
+            *largest -= step;
+        }
+        Self::from_squares(squares, &amounts)
+    }
+
+    /// Squares from deploy groups
+    pub fn from_groups(groups: &[DeployGroup]) -> Self {
+        let mut amounts = [0u64; 25];
+        for group in groups {
+            for i in group.indices() {
+                amounts[i as usize] = group.amount;
+            }
+        }
+        Self { amounts }
+    }
+
+    /// Every amount scaled by `factor` (<= 1); squares that fall below the
+    /// program minimum are dropped
+    pub fn scaled(&self, factor: f64) -> Self {
+        let mut amounts = self.amounts;
+        for amount in amounts.iter_mut() {
+            let scaled = (*amount as f64 * factor.clamp(0.0, 1.0)) as u64;
+            *amount = if scaled >= MIN_PER_SQUARE { scaled } else { 0 };
+        }
+        Self { amounts }
+    }
+
+    /// Lamports on each square
+    pub fn amounts(&self) -> &[u64; 25] {
+        &self.amounts
+    }
+
+    /// Deployed square indices
+    pub fn squares(&self) -> Vec<u8> {
+        (0..25u8).filter(|&i| self.amounts[i as usize] > 0).collect()
+    }
+
+    /// Lamports across all squares
+    pub fn total(&self) -> u64 {
+        self.amounts.iter().sum()
+    }
+
+    /// Largest amount on any square
+    pub fn max_amount(&self) -> u64 {
+        self.amounts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Squares grouped by amount, largest amount first
+    pub fn groups(&self) -> Vec<DeployGroup> {
+        let mut groups: Vec<DeployGroup> = Vec::new();
+        for (i, &amount) in self.amounts.iter().enumerate().filter(|(_, &a)| a > 0) {
+            match groups.iter_mut().find(|g| g.amount == amount) {
+                Some(group) => group.squares[i] = true,
+                None => {
+                    let mut squares = [false; 25];
+                    squares[i] = true;
+                    groups.push(DeployGroup { amount, squares });
+                }
+            }
+        }
+        groups.sort_by(|a, b| b.amount.cmp(&a.amount));
+        groups
+    }
+}
+
+/// Split instructions into as few transactions as fit, in order. `prefix`
+/// builds the instructions that lead each transaction (compute budget) for a
+/// given instruction count.
+pub fn pack(
+    payer: &Pubkey,
+    prefix: impl Fn(usize) -> Vec<Instruction>,
+    instructions: Vec<Instruction>,
+) -> Vec<Vec<Instruction>> {
+    let fits = |chunk: &[Instruction]| {
+        let mut all = prefix(chunk.len());
+        all.extend_from_slice(chunk);
+        let tx = Transaction::new_with_payer(&all, Some(payer));
+        bincode::serialized_size(&tx).map(|size| size as usize <= PACKET_DATA_SIZE).unwrap_or(false)
+    };
+
+    let mut chunks: Vec<Vec<Instruction>> = Vec::new();
+    let mut current: Vec<Instruction> = Vec::new();
+    for ix in instructions {
+        current.push(ix);
+        if current.len() > MAX_DEPLOYS_PER_TX || (current.len() > 1 && !fits(&current)) {
+            let ix = current.pop().expect("just pushed");
+            chunks.push(std::mem::take(&mut current));
+            current.push(ix);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    const SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_thin_allocation_favours_thin_squares() {
+        let mut deployed = vec![SOL; 25];
+        deployed[0] = 0;
+        deployed[2] = 10 * SOL;
+        let base = SOL / 100;
+        let allocation = Allocation::thin(&deployed, &[0, 1, 2], base);
+        let amounts = allocation.amounts();
+
+        assert!(amounts[0] > amounts[1] && amounts[1] >= amounts[2], "{:?}", &amounts[..3]);
+        assert!(amounts[..3].iter().all(|&a| a >= base / 4 && a % (base / 4) == 0));
+        assert_eq!(allocation.squares(), vec![0, 1, 2]);
+        // Equal stakes get equal amounts
+        assert_eq!(Allocation::thin(&[SOL; 25], &[3, 4], base), Allocation::uniform(&[3, 4], base));
+    }
+
+    #[test]
+    fn test_weighted_allocation_follows_weights() {
+        let base = SOL / 100;
+        let allocation = Allocation::weighted(&[2, 7], &[0.75, 0.25], base);
+        assert_eq!(allocation.amounts()[2], base * 3 / 2);
+        assert_eq!(allocation.amounts()[7], base / 2);
+        assert_eq!(allocation.total(), 2 * base);
+        // Squares raised to the one-step floor don't push the total over
+        let skewed = Allocation::weighted(&[0, 1, 2], &[1.0, 1e-9, 1e-9], base);
+        assert_eq!(skewed.total(), 3 * base);
+        assert_eq!(skewed.amounts()[1], base / 4);
+        // Mismatched or all-zero weights spread evenly
+        assert_eq!(Allocation::weighted(&[2, 7], &[1.0], base), Allocation::uniform(&[2, 7], base));
+        assert_eq!(Allocation::weighted(&[2, 7], &[0.0, 0.0], base), Allocation::uniform(&[2, 7], base));
+    }
+
+    #[test]
+    fn test_groups_share_amounts() {
+        let allocation = Allocation::from_squares(&[1, 5, 9], &[300, 100, 300]);
+        let groups = allocation.groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!((groups[0].amount, groups[0].indices()), (300, vec![1, 9]));
+        assert_eq!((groups[1].amount, groups[1].indices()), (100, vec![5]));
+        assert_eq!(allocation.total(), 700);
+        assert_eq!(groups.iter().map(|g| g.total()).sum::<u64>(), 700);
+        assert_eq!(Allocation::from_groups(&groups), allocation);
+    }
+
+    #[test]
+    fn test_pack_fits_packet_size() {
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        // Each instruction brings its own accounts, so a transaction fills up quickly
+        let instructions: Vec<Instruction> = (0..12u8)
+            .map(|i| {
+                let accounts = (0..6).map(|_| AccountMeta::new(Pubkey::new_unique(), false)).collect();
+                Instruction::new_with_bytes(program, &[i; 16], accounts)
+            })
+            .collect();
+        let chunks = pack(&payer, |_| Vec::new(), instructions.clone());
+
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), instructions);
+        for chunk in &chunks {
+            let tx = Transaction::new_with_payer(chunk, Some(&payer));
+            assert!(bincode::serialized_size(&tx).unwrap() as usize <= PACKET_DATA_SIZE);
+        }
+        // Shared accounts pack densely, up to the compute cap
+        let shared: Vec<Instruction> = (0..10u8).map(|i| Instruction::new_with_bytes(program, &[i], vec![])).collect();
+        assert_eq!(pack(&payer, |_| Vec::new(), shared).len(), 2);
+    }
+}
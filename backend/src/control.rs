@@ -0,0 +1,354 @@
+//! Session Mailboxes
+//!
+//! Each running session is an actor: its mining loop owns the session's
+//! config and counters, and everything else talks to it through a mailbox.
+//! The loop answers messages while it waits on the chain, so a stop takes
+//! effect at once and status queries see live counters. Pauses and config
+//! changes apply from the next round; a round already under way finishes
+//! with the config it started with, and the session keeps its `sessions`
+//! row and counters.
+
+use std::future::Future;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::strategy::{RoundDecision, SessionConfig};
+use crate::Strategy;
+
+/// Longest a caller waits for a session to answer; the loop answers while
+/// it waits on anything, deploys being sent included
+const ASK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A message to a running session
+#[derive(Debug)]
+pub enum SessionMessage {
+    Stop,
+    /// Sit out rounds until resumed
+    Pause(oneshot::Sender<Result<()>>),
+    Resume(oneshot::Sender<Result<()>>),
+    /// Change the config from the next round; answers with the new config
+    /// and the fields that changed
+    UpdateConfig(ConfigUpdate, oneshot::Sender<Result<(SessionConfig, serde_json::Value)>>),
+    QueryStatus(oneshot::Sender<SessionStatus>),
+    /// Winnings of a settled round, from the session's own round resolution
+    Settled(u64),
+}
+
+/// Send a message and wait for the session's answer
+pub async fn ask<T>(
+    mailbox: &mpsc::UnboundedSender<SessionMessage>,
+    message: impl FnOnce(oneshot::Sender<T>) -> SessionMessage,
+) -> Result<T> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    mailbox.send(message(reply_tx)).map_err(|_| anyhow::anyhow!("Session's mining loop has exited"))?;
+    tokio::time::timeout(ASK_TIMEOUT, reply_rx)
+        .await
+        .context("Session did not answer in time")?
+        .context("Session's mining loop has exited")
+}
+
+/// Config fields that can change while a session runs (lamports); `None`
+/// leaves a field as it is
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigUpdate {
+    pub deploy_amount: Option<u64>,
+    pub max_tip: Option<u64>,
+    pub num_blocks: Option<u8>,
+    pub strategy: Option<Strategy>,
+}
+
+impl ConfigUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.deploy_amount.is_none() && self.max_tip.is_none() && self.num_blocks.is_none() && self.strategy.is_none()
+    }
+
+    pub fn apply(&self, config: &mut SessionConfig) {
+        if let Some(amount) = self.deploy_amount {
+            config.deploy_amount = amount;
+        }
+        if let Some(tip) = self.max_tip {
+            config.max_tip = tip;
+        }
+        if let Some(n) = self.num_blocks {
+            config.num_blocks = n.clamp(1, 25);
+        }
+        if let Some(strategy) = &self.strategy {
+            config.strategy = strategy.clone();
+        }
+    }
+
+    /// The fields the update sets on `config`, as `{"field": {"from": .., "to": ..}}`
+    pub fn changes(&self, config: &SessionConfig) -> serde_json::Value {
+        let mut after = config.clone();
+        self.apply(&mut after);
+        let mut changes = serde_json::Map::new();
+        let mut note = |field: &str, from: serde_json::Value, to: serde_json::Value| {
+            if from != to {
+                changes.insert(field.to_string(), serde_json::json!({ "from": from, "to": to }));
+            }
+        };
+        note("deploy_amount", config.deploy_amount.into(), after.deploy_amount.into());
+        note("max_tip", config.max_tip.into(), after.max_tip.into());
+        note("num_blocks", config.num_blocks.into(), after.num_blocks.into());
+        note("strategy", config.strategy.as_str().into(), after.strategy.as_str().into());
+        serde_json::Value::Object(changes)
+    }
+
+    /// The update that puts back the `from` side of `changes`
+    pub fn undo(changes: &serde_json::Value) -> ConfigUpdate {
+        let from = |field: &str| changes.get(field).and_then(|change| change.get("from"));
+        ConfigUpdate {
+            deploy_amount: from("deploy_amount").and_then(|v| v.as_u64()),
+            max_tip: from("max_tip").and_then(|v| v.as_u64()),
+            num_blocks: from("num_blocks").and_then(|v| v.as_u64()).map(|n| n as u8),
+            strategy: from("strategy").and_then(|v| serde_json::from_value(v.clone()).ok()),
+        }
+    }
+}
+
+/// Counters kept by a running session (lamports); a fleet counts each of
+/// its rounds once, across all of its wallets
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SessionCounters {
+    pub rounds_played: u64,
+    pub rounds_skipped: u64,
+    pub total_deployed: u64,
+    pub total_tips: u64,
+    pub total_won: u64,
+}
+
+/// Where a session is in the round
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPhase {
+    /// Waiting for the submission window (or paused)
+    #[default]
+    Waiting,
+    /// Snapshotting the grid and deciding
+    InWindow,
+    Submitting,
+    /// Deployed; waiting for the round to end
+    AwaitingResult,
+}
+
+/// A session's latest decision
+#[derive(Debug, Clone, Serialize)]
+pub struct LastDecision {
+    pub round_id: u64,
+    #[serde(flatten)]
+    pub decision: RoundDecision,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// What a running session is doing
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LiveState {
+    pub phase: SessionPhase,
+    /// Round the session is working on
+    pub round_id: Option<u64>,
+    pub last_decision: Option<LastDecision>,
+    pub last_signature: Option<String>,
+    /// Errors (RPC, building or sending a deploy) since the last round that
+    /// went by without one
+    pub error_streak: u32,
+    pub last_error: Option<String>,
+}
+
+/// A running session as it answers a status query
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStatus {
+    pub session_id: Uuid,
+    pub wallet: String,
+    pub strategy: String,
+    pub deploy_amount: u64,
+    pub max_tip: u64,
+    pub num_blocks: u8,
+    pub paused: bool,
+    #[serde(flatten)]
+    pub counters: SessionCounters,
+    #[serde(flatten)]
+    pub live: LiveState,
+    /// Read from the chain by the API, not the session
+    pub automation_balance: Option<u64>,
+    /// Seconds until the current round ends, read from the chain by the API
+    pub next_round_eta_secs: Option<f64>,
+}
+
+/// A session's mailbox and the state it answers from, owned by its mining loop
+pub struct Mailbox {
+    rx: mpsc::UnboundedReceiver<SessionMessage>,
+    /// Config future rounds start from
+    pub config: SessionConfig,
+    pub paused: bool,
+    pub counters: SessionCounters,
+    pub live: LiveState,
+}
+
+impl Mailbox {
+    pub fn new(rx: mpsc::UnboundedReceiver<SessionMessage>, config: SessionConfig) -> Self {
+        Self { rx, config, paused: false, counters: SessionCounters::default(), live: LiveState::default() }
+    }
+
+    /// Count an error toward the error streak
+    pub fn error(&mut self, error: impl ToString) {
+        self.live.error_streak += 1;
+        self.live.last_error = Some(error.to_string());
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        SessionStatus {
+            session_id: self.config.session_id,
+            wallet: self.config.wallet.clone(),
+            strategy: self.config.strategy.as_str().to_string(),
+            deploy_amount: self.config.deploy_amount,
+            max_tip: self.config.max_tip,
+            num_blocks: self.config.num_blocks,
+            paused: self.paused,
+            counters: self.counters,
+            live: self.live.clone(),
+            automation_balance: None,
+            next_round_eta_secs: None,
+        }
+    }
+
+    /// Handle a message; false once the session is told to stop (or every
+    /// sender is gone)
+    fn handle(&mut self, message: Option<SessionMessage>) -> bool {
+        match message {
+            None | Some(SessionMessage::Stop) => return false,
+            Some(SessionMessage::Pause(reply)) => {
+                let _ = reply.send(self.set_paused(true));
+            }
+            Some(SessionMessage::Resume(reply)) => {
+                let _ = reply.send(self.set_paused(false));
+            }
+            Some(SessionMessage::UpdateConfig(update, reply)) => {
+                let _ = reply.send(self.update(update));
+            }
+            Some(SessionMessage::QueryStatus(reply)) => {
+                let _ = reply.send(self.status());
+            }
+            Some(SessionMessage::Settled(won)) => self.counters.total_won += won,
+        }
+        true
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<()> {
+        if self.paused == paused {
+            anyhow::bail!("Session is already {}", if paused { "paused" } else { "running" });
+        }
+        self.paused = paused;
+        Ok(())
+    }
+
+    fn update(&mut self, update: ConfigUpdate) -> Result<(SessionConfig, serde_json::Value)> {
+        if self.config.motherlode.is_some() && (update.deploy_amount.is_some() || update.num_blocks.is_some()) {
+            anyhow::bail!("Motherlode sessions set their own stake and square count");
+        }
+        // Every round's arm would overwrite them
+        if self.config.experiment.is_some() && (update.deploy_amount.is_some() || update.num_blocks.is_some()) {
+            anyhow::bail!("Experiment sessions take their stake and square count from their arm");
+        }
+        let changes = update.changes(&self.config);
+        update.apply(&mut self.config);
+        Ok((self.config.clone(), changes))
+    }
+
+    /// Handle every message waiting; false if the session is to stop
+    pub fn drain(&mut self) -> bool {
+        loop {
+            match self.rx.try_recv() {
+                Ok(message) => {
+                    if !self.handle(Some(message)) {
+                        return false;
+                    }
+                }
+                Err(mpsc::error::TryRecvError::Empty) => return true,
+                Err(mpsc::error::TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    /// Wait for the next message; false if the session is to stop
+    pub async fn next(&mut self) -> bool {
+        let message = self.rx.recv().await;
+        self.handle(message)
+    }
+
+    /// Run `fut`, answering messages meanwhile. None if the session is told
+    /// to stop first, in which case `fut` is dropped
+    pub async fn run<F: Future>(&mut self, fut: F) -> Option<F::Output> {
+        tokio::pin!(fut);
+        loop {
+            tokio::select! {
+                output = &mut fut => return Some(output),
+                message = self.rx.recv() => {
+                    if !self.handle(message) {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SessionConfig {
+        SessionConfig::new(Uuid::new_v4(), "wallet".to_string(), Strategy::BestEv, 0.01, 0.001, 3)
+    }
+
+    #[test]
+    fn test_update_applies_and_reports_changes() {
+        let update = ConfigUpdate { deploy_amount: Some(20_000_000), num_blocks: Some(40), ..Default::default() };
+        let mut after = config();
+        let changes = update.changes(&after);
+        assert_eq!(changes["deploy_amount"]["from"], 10_000_000);
+        assert_eq!(changes["deploy_amount"]["to"], 20_000_000);
+        assert_eq!(changes["num_blocks"]["to"], 25);
+        assert!(changes.get("max_tip").is_none());
+
+        update.apply(&mut after);
+        assert_eq!((after.deploy_amount, after.num_blocks, after.max_tip), (20_000_000, 25, 1_000_000));
+        assert!(ConfigUpdate::default().is_empty());
+
+        // Undoing restores the fields that changed, and only those
+        let mut restored = after.clone();
+        restored.strategy = Strategy::Aggressive;
+        let changes = ConfigUpdate { strategy: Some(Strategy::Aggressive), ..update }.changes(&config());
+        ConfigUpdate::undo(&changes).apply(&mut restored);
+        assert_eq!((restored.deploy_amount, restored.num_blocks, restored.max_tip), (10_000_000, 3, 1_000_000));
+        assert_eq!(restored.strategy.as_str(), "best_ev");
+        assert!(ConfigUpdate::undo(&serde_json::json!({})).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mailbox_answers_while_waiting_and_stops() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut mailbox = Mailbox::new(rx, config());
+
+        // Messages are answered while the loop waits, then the wait finishes
+        let asker = tokio::spawn(async move {
+            ask(&tx, SessionMessage::Pause).await.unwrap().unwrap();
+            assert!(ask(&tx, SessionMessage::Pause).await.unwrap().is_err(), "already paused");
+            tx.send(SessionMessage::Settled(5)).unwrap();
+            let status = ask(&tx, SessionMessage::QueryStatus).await.unwrap();
+            assert!(status.paused);
+            assert_eq!(status.live.phase, SessionPhase::Waiting);
+            tx
+        });
+        let tx = mailbox.run(async { asker.await.unwrap() }).await.unwrap();
+        assert_eq!(mailbox.counters.total_won, 5);
+
+        // A stop drops the wait
+        tx.send(SessionMessage::Stop).unwrap();
+        assert!(mailbox.run(std::future::pending::<()>()).await.is_none());
+    }
+}
@@ -58,6 +58,22 @@ pub struct Transaction {
     pub actual_reward: Option<i64>,
     pub status: String,
     pub strategy: String,
+    /// Block selection source: fresh, speculative or fallback
+    pub selection_source: Option<String>,
+    /// Latency of the AI call that produced the selection
+    pub selection_latency_ms: Option<i64>,
+    /// Deadline margin of the selection; negative = late
+    pub selection_margin_ms: Option<i64>,
+    /// Failure cause label when the deploy failed
+    pub failure_cause: Option<String>,
+    /// Full diagnosis of a failed deploy
+    pub diagnosis: Option<serde_json::Value>,
+    /// Every square the deploy covered
+    pub squares: Option<Vec<i16>>,
+    pub experiment_id: Option<Uuid>,
+    pub experiment_arm: Option<String>,
+    /// ORE earned if the deploy won (grains)
+    pub ore_reward: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -66,6 +82,8 @@ pub struct Transaction {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TxStatus {
     Pending,
+    /// Landed; not yet settled
+    Confirmed,
     Won,
     Lost,
     Skipped,
@@ -76,6 +94,7 @@ impl TxStatus {
     pub fn as_str(&self) -> &'static str {
         match self {
             TxStatus::Pending => "pending",
+            TxStatus::Confirmed => "confirmed",
             TxStatus::Won => "won",
             TxStatus::Lost => "lost",
             TxStatus::Skipped => "skipped",
@@ -138,6 +157,218 @@ pub struct SessionStats {
     pub total_won: i64,
     pub net_pnl: i64,
     pub win_rate: f64,
+    /// ORE earned by settled deploys (grains)
+    pub ore_earned: i64,
+    /// Rewards minus deploys and tips of settled deploys (lamports)
+    pub settled_pnl: i64,
+}
+
+impl SessionStats {
+    /// Combined stats of a fleet's wallets. Every wallet counts the rounds the
+    /// fleet sat out, so skips are the most any wallet counted
+    pub fn combine(stats: &[SessionStats]) -> SessionStats {
+        let sum = |f: fn(&SessionStats) -> i64| stats.iter().map(f).sum::<i64>();
+        let rounds_won = sum(|s| s.rounds_won);
+        let rounds_lost = sum(|s| s.rounds_lost);
+        SessionStats {
+            rounds_played: sum(|s| s.rounds_played),
+            rounds_skipped: stats.iter().map(|s| s.rounds_skipped).max().unwrap_or(0),
+            rounds_won,
+            rounds_lost,
+            total_deployed: sum(|s| s.total_deployed),
+            total_tips: sum(|s| s.total_tips),
+            total_won: sum(|s| s.total_won),
+            net_pnl: sum(|s| s.net_pnl),
+            win_rate: if rounds_won + rounds_lost > 0 {
+                rounds_won as f64 / (rounds_won + rounds_lost) as f64
+            } else {
+                0.0
+            },
+            ore_earned: sum(|s| s.ore_earned),
+            settled_pnl: sum(|s| s.settled_pnl),
+        }
+    }
+}
+
+/// AI block selection audit record
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AiDecision {
+    pub id: Uuid,
+    pub model: String,
+    pub round_id: i64,
+    pub grid: serde_json::Value,
+    pub prompt: Option<String>,
+    pub raw_response: Option<String>,
+    pub selection: Option<serde_json::Value>,
+    pub selected_blocks: Vec<i16>,
+    pub skip: bool,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    /// Aborted at the decision deadline
+    pub timed_out: bool,
+    /// Answer used in place of a timed-out call (speculative or fallback)
+    pub used_instead: Option<String>,
+    pub experiment_id: Option<Uuid>,
+    pub experiment_arm: Option<String>,
+    pub winning_square: Option<i16>,
+    /// None until the round resolves, and for skips and errors
+    pub won: Option<bool>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// New AI decision to record
+#[derive(Debug, Clone)]
+pub struct NewAiDecision {
+    pub model: String,
+    pub round_id: i64,
+    pub grid: serde_json::Value,
+    pub prompt: Option<String>,
+    pub raw_response: Option<String>,
+    pub selection: Option<serde_json::Value>,
+    pub selected_blocks: Vec<i16>,
+    pub skip: bool,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    pub timed_out: bool,
+    pub used_instead: Option<String>,
+    pub experiment_id: Option<Uuid>,
+    pub experiment_arm: Option<String>,
+}
+
+/// Aggregate AI accuracy for one model
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AiModelAccuracy {
+    pub model: String,
+    pub decisions: i64,
+    /// Decisions with a known outcome (excludes skips and errors)
+    pub resolved: i64,
+    pub wins: i64,
+    pub skips: i64,
+    pub errors: i64,
+    /// Calls aborted at the decision deadline (counted in `errors`)
+    pub timeouts: i64,
+    pub win_rate: Option<f64>,
+    /// Win rate of picking the same number of squares at random
+    pub expected_win_rate: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+}
+
+/// Stored strategy experiment (arms and assignment as JSON)
+#[derive(Debug, Clone, FromRow)]
+pub struct ExperimentRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub assignment: serde_json::Value,
+    pub arms: serde_json::Value,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// Deploy tagged with an experiment arm
+#[derive(Debug, Clone, FromRow)]
+pub struct ExperimentTransaction {
+    pub experiment_arm: String,
+    pub round_id: i64,
+    pub status: String,
+    pub deploy_amount: i64,
+    pub tip_amount: i64,
+    pub actual_reward: Option<i64>,
+    pub squares: Vec<i16>,
+}
+
+impl ExperimentTransaction {
+    /// Lamports spent: the per-square deploy on every square, plus the tip
+    pub fn cost(&self) -> i64 {
+        self.deploy_amount * self.squares.len().max(1) as i64 + self.tip_amount
+    }
+    
+    pub fn pnl(&self) -> i64 {
+        self.actual_reward.unwrap_or(0) - self.cost()
+    }
+}
+
+/// A session's strategy script
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SessionScript {
+    pub session_id: Uuid,
+    pub source: String,
+    pub version: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A round a session sat out
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct RoundSkip {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_wallet: String,
+    pub round_id: i64,
+    pub reason: String,
+    /// Motherlode at decision time (grains)
+    pub motherlode: i64,
+    pub experiment_id: Option<Uuid>,
+    pub experiment_arm: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A pause, resume or config change made to a running session
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SessionChange {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_wallet: String,
+    pub action: String,
+    pub changes: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A fleet of wallets mining as one session
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FleetRecord {
+    pub id: Uuid,
+    pub lead_wallet: String,
+    pub split: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// Stored session schedule (spec and session request as JSON)
+#[derive(Debug, Clone, FromRow)]
+pub struct ScheduleRecord {
+    pub id: Uuid,
+    pub wallet: String,
+    pub spec: serde_json::Value,
+    pub session: serde_json::Value,
+    pub max_rounds: Option<i64>,
+    pub until_at: Option<DateTime<Utc>>,
+    pub enabled: bool,
+    pub session_id: Option<Uuid>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Persistent risk state: `global` (kill switch) or a wallet (cooldown)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct RiskState {
+    pub scope: String,
+    pub blocked: bool,
+    /// Cooldown ends at this round (wallet scope)
+    pub until_round: Option<i64>,
+    pub reason: Option<String>,
+    /// Aggregate drawdown is measured from here (global scope)
+    pub baseline_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Grid snapshot paired with its round's final stakes (local model training)
+#[derive(Debug, Clone, FromRow)]
+pub struct TrainingRow {
+    pub grid: serde_json::Value,
+    pub final_deployed: Vec<i64>,
 }
 
 // =============================================================================
@@ -156,267 +387,1298 @@ impl Database {
     }
     
     // =========================================================================
-    // Session Operations
+    // Session Operations
+    // =========================================================================
+    
+    /// Create a new mining session
+    /// Amounts are in lamports (1 SOL = 1_000_000_000 lamports)
+    pub async fn create_session(
+        &self,
+        wallet: &str,
+        strategy: Strategy,
+        max_tip: i64,
+        deploy_amount: i64,
+        budget: i64,
+    ) -> Result<Session> {
+        let strategy_str = strategy.as_str();
+        
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            INSERT INTO sessions (
+                id, user_wallet, strategy, max_tip, deploy_amount, budget,
+                rounds_played, rounds_skipped, total_deployed, total_tips,
+                total_won, net_pnl, is_active, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, 0, 0, 0, 0, 0, 0, true, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(wallet)
+        .bind(strategy_str)
+        .bind(max_tip)
+        .bind(deploy_amount)
+        .bind(budget)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to create session")?;
+        
+        info!("Created session {} for wallet {}", session.id, wallet);
+        Ok(session)
+    }
+    
+    /// End a mining session
+    pub async fn end_session(&self, wallet: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET is_active = false, updated_at = NOW() WHERE user_wallet = $1 AND is_active = true"
+        )
+        .bind(wallet)
+        .execute(&self.pool)
+        .await
+        .context("Failed to end session")?;
+        
+        Ok(())
+    }
+    
+    /// Change a running session's strategy, max tip and deploy amount (lamports)
+    pub async fn update_session_config(
+        &self,
+        session_id: Uuid,
+        strategy: Strategy,
+        max_tip: i64,
+        deploy_amount: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET strategy = $2, max_tip = $3, deploy_amount = $4, updated_at = NOW() WHERE id = $1"
+        )
+        .bind(session_id)
+        .bind(strategy.as_str())
+        .bind(max_tip)
+        .bind(deploy_amount)
+        .execute(&self.pool)
+        .await
+        .context("Failed to update session config")?;
+        
+        Ok(())
+    }
+    
+    /// Record a pause, resume or config change in the session's audit trail
+    pub async fn record_session_change(
+        &self,
+        session_id: Uuid,
+        wallet: &str,
+        action: &str,
+        changes: &serde_json::Value,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO session_changes (id, session_id, user_wallet, action, changes, created_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(session_id)
+        .bind(wallet)
+        .bind(action)
+        .bind(changes)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record session change")?;
+        
+        Ok(())
+    }
+    
+    /// A session's audit trail, newest first
+    pub async fn get_session_changes(&self, session_id: Uuid, limit: i64) -> Result<Vec<SessionChange>> {
+        let changes = sqlx::query_as::<_, SessionChange>(
+            "SELECT * FROM session_changes WHERE session_id = $1 ORDER BY created_at DESC LIMIT $2",
+        )
+        .bind(session_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch session changes")?;
+        
+        Ok(changes)
+    }
+    
+    /// Get active session for wallet
+    pub async fn get_active_session(&self, wallet: &str) -> Result<Option<Session>> {
+        let session = sqlx::query_as::<_, Session>(
+            "SELECT * FROM sessions WHERE user_wallet = $1 AND is_active = true ORDER BY created_at DESC LIMIT 1"
+        )
+        .bind(wallet)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch session")?;
+        
+        Ok(session)
+    }
+    
+    /// Update session statistics (amounts in lamports)
+    pub async fn update_session_stats(
+        &self,
+        session_id: Uuid,
+        deployed: i64,
+        tip: i64,
+        reward: Option<i64>,
+        is_skip: bool,
+    ) -> Result<()> {
+        let won = reward.unwrap_or(0);
+        
+        if is_skip {
+            sqlx::query(
+                r#"
+                UPDATE sessions SET
+                    rounds_skipped = rounds_skipped + 1,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        } else {
+            let net = won - deployed - tip;
+            sqlx::query(
+                r#"
+                UPDATE sessions SET
+                    rounds_played = rounds_played + 1,
+                    total_deployed = total_deployed + $2,
+                    total_tips = total_tips + $3,
+                    total_won = total_won + $4,
+                    net_pnl = net_pnl + $5,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(session_id)
+            .bind(deployed)
+            .bind(tip)
+            .bind(won)
+            .bind(net)
+            .execute(&self.pool)
+            .await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Get session stats
+    pub async fn get_session_stats(&self, session_id: Uuid) -> Result<SessionStats> {
+        let session = sqlx::query_as::<_, Session>(
+            "SELECT * FROM sessions WHERE id = $1"
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .context("Session not found")?;
+        
+        let rounds_won = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM transactions WHERE session_id = $1 AND status = 'won'"
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0);
+        
+        let rounds_lost = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM transactions WHERE session_id = $1 AND status = 'lost'"
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0);
+        
+        let ore_earned = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(SUM(ore_reward), 0)::BIGINT FROM transactions WHERE session_id = $1"
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0);
+        
+        // deploy_amount is per square
+        let settled_pnl = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COALESCE(SUM(
+                COALESCE(actual_reward, 0) - deploy_amount * GREATEST(COALESCE(cardinality(squares), 1), 1) - tip_amount
+            ), 0)::BIGINT
+            FROM transactions
+            WHERE session_id = $1 AND status IN ('won', 'lost')
+            "#,
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0);
+        
+        let total_rounds = rounds_won + rounds_lost;
+        let win_rate = if total_rounds > 0 {
+            rounds_won as f64 / total_rounds as f64
+        } else {
+            0.0
+        };
+        
+        Ok(SessionStats {
+            rounds_played: session.rounds_played,
+            rounds_skipped: session.rounds_skipped,
+            rounds_won,
+            rounds_lost,
+            total_deployed: session.total_deployed,
+            total_tips: session.total_tips,
+            total_won: session.total_won,
+            net_pnl: session.net_pnl,
+            win_rate,
+            ore_earned,
+            settled_pnl,
+        })
+    }
+    
+    /// Consecutive losing rounds since the session's last win (settled deploys only)
+    pub async fn get_loss_streak(&self, session_id: Uuid) -> Result<i64> {
+        let streak: i64 = sqlx::query_scalar(
+            r#"
+            WITH rounds AS (
+                SELECT round_id, BOOL_OR(status = 'won') AS won
+                FROM transactions
+                WHERE session_id = $1 AND status IN ('won', 'lost')
+                GROUP BY round_id
+            )
+            SELECT COUNT(*) FROM rounds
+            WHERE NOT won AND round_id > COALESCE((SELECT MAX(round_id) FROM rounds WHERE won), -1)
+            "#,
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to fetch loss streak")?;
+        
+        Ok(streak)
+    }
+    
+    // =========================================================================
+    // Transaction Operations
+    // =========================================================================
+    
+    /// Record a new transaction (amounts in lamports)
+    pub async fn record_transaction(
+        &self,
+        wallet: &str,
+        session_id: Option<Uuid>,
+        round_id: i64,
+        block_index: i16,
+        deploy_amount: i64,
+        tip_amount: i64,
+        expected_ev: i64,
+        strategy: &str,
+    ) -> Result<Transaction> {
+        let tx = sqlx::query_as::<_, Transaction>(
+            r#"
+            INSERT INTO transactions (
+                id, user_wallet, session_id, round_id, block_index,
+                deploy_amount, tip_amount, expected_ev, status, strategy,
+                created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'pending', $9, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(wallet)
+        .bind(session_id)
+        .bind(round_id)
+        .bind(block_index)
+        .bind(deploy_amount)
+        .bind(tip_amount)
+        .bind(expected_ev)
+        .bind(strategy)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to record transaction")?;
+        
+        debug!("Recorded transaction {} for round {}", tx.id, round_id);
+        Ok(tx)
+    }
+    
+    /// Update transaction status
+    pub async fn update_transaction_status(
+        &self,
+        tx_id: Uuid,
+        status: TxStatus,
+        signature: Option<&str>,
+        reward: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE transactions SET
+                status = $2,
+                tx_signature = COALESCE($3, tx_signature),
+                actual_reward = $4,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(tx_id)
+        .bind(status.as_str())
+        .bind(signature)
+        .bind(reward)
+        .execute(&self.pool)
+        .await
+        .context("Failed to update transaction")?;
+        
+        Ok(())
+    }
+    
+    /// Record where a deploy's block selection came from and how late it was
+    pub async fn record_selection_source(
+        &self,
+        tx_id: Uuid,
+        source: &str,
+        latency_ms: Option<i64>,
+        margin_ms: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE transactions SET
+                selection_source = $2,
+                selection_latency_ms = $3,
+                selection_margin_ms = $4,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(tx_id)
+        .bind(source)
+        .bind(latency_ms)
+        .bind(margin_ms)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record selection source")?;
+        
+        Ok(())
+    }
+    
+    /// Mark a transaction failed and attach its diagnosis
+    pub async fn record_transaction_failure(
+        &self,
+        tx_id: Uuid,
+        failure_cause: &str,
+        diagnosis: &serde_json::Value,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE transactions SET
+                status = 'failed',
+                failure_cause = $2,
+                diagnosis = $3,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(tx_id)
+        .bind(failure_cause)
+        .bind(diagnosis)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record transaction failure")?;
+        
+        Ok(())
+    }
+    
+    /// Attach a deploy's squares and experiment arm
+    pub async fn tag_transaction(
+        &self,
+        tx_id: Uuid,
+        squares: &[i16],
+        experiment_id: Option<Uuid>,
+        experiment_arm: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE transactions SET
+                squares = $2,
+                experiment_id = $3,
+                experiment_arm = $4,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(tx_id)
+        .bind(squares)
+        .bind(experiment_id)
+        .bind(experiment_arm)
+        .execute(&self.pool)
+        .await
+        .context("Failed to tag transaction")?;
+        
+        Ok(())
+    }
+    
+    /// Settle a round's pending and confirmed deploys as won or lost, and
+    /// credit their winnings to their sessions; returns how many were settled
+    ///
+    /// A winning deploy gets its stake back plus its pro-rata share of the
+    /// round's winnings and of its ORE reward (grains).
+    pub async fn settle_round_transactions(
+        &self,
+        round_id: i64,
+        winning_square: i16,
+        winning_square_total: i64,
+        total_winnings: i64,
+        ore_reward: i64,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let settled = sqlx::query_as::<_, (Option<Uuid>, i64)>(
+            r#"
+            UPDATE transactions SET
+                status = CASE WHEN $2 = ANY(squares) THEN 'won' ELSE 'lost' END,
+                actual_reward = CASE
+                    WHEN $2 = ANY(squares) THEN
+                        deploy_amount + (deploy_amount::NUMERIC * $4 / NULLIF($3, 0))::BIGINT
+                    ELSE 0
+                END,
+                ore_reward = CASE
+                    WHEN $2 = ANY(squares) THEN (deploy_amount::NUMERIC * $5 / NULLIF($3, 0))::BIGINT
+                    ELSE 0
+                END,
+                updated_at = NOW()
+            WHERE round_id = $1 AND status IN ('pending', 'confirmed') AND squares IS NOT NULL
+            RETURNING session_id, COALESCE(actual_reward, 0)::BIGINT
+            "#,
+        )
+        .bind(round_id)
+        .bind(winning_square)
+        .bind(winning_square_total)
+        .bind(total_winnings)
+        .bind(ore_reward)
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to settle round transactions")?;
+        
+        // Deploys and tips were counted when sent; winnings count once the round settles
+        let mut won: std::collections::HashMap<Uuid, i64> = std::collections::HashMap::new();
+        for (session_id, reward) in &settled {
+            if let Some(session_id) = session_id {
+                *won.entry(*session_id).or_default() += reward;
+            }
+        }
+        for (session_id, won) in won.into_iter().filter(|(_, won)| *won > 0) {
+            sqlx::query(
+                r#"
+                UPDATE sessions SET
+                    total_won = total_won + $2,
+                    net_pnl = net_pnl + $2,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(session_id)
+            .bind(won)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to credit session winnings")?;
+        }
+        tx.commit().await?;
+        
+        Ok(settled.len() as u64)
+    }
+    
+    /// Mark a sent deploy as landed, unless it has already been settled
+    pub async fn mark_transaction_confirmed(&self, tx_id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE transactions SET status = $2, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(tx_id)
+        .bind(TxStatus::Confirmed.as_str())
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark transaction confirmed")?;
+        
+        Ok(())
+    }
+    
+    /// Mark a deploy that never landed as failed. It may already have been
+    /// settled while it was watched, so winnings credited for it are taken back
+    pub async fn mark_transaction_unlanded(&self, tx_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let settled = sqlx::query_as::<_, (Option<Uuid>, i64)>(
+            r#"
+            WITH prior AS (
+                SELECT id, session_id, status, COALESCE(actual_reward, 0)::BIGINT AS reward
+                FROM transactions WHERE id = $1 AND status IN ('pending', 'won', 'lost')
+                FOR UPDATE
+            )
+            UPDATE transactions t SET
+                status = $2,
+                actual_reward = NULL,
+                ore_reward = NULL,
+                updated_at = NOW()
+            FROM prior
+            WHERE t.id = prior.id
+            RETURNING prior.session_id, CASE WHEN prior.status = 'won' THEN prior.reward ELSE 0 END
+            "#,
+        )
+        .bind(tx_id)
+        .bind(TxStatus::Failed.as_str())
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to mark transaction unlanded")?;
+        
+        if let Some((Some(session_id), won)) = settled.filter(|(_, won)| *won > 0) {
+            sqlx::query(
+                r#"
+                UPDATE sessions SET
+                    total_won = total_won - $2,
+                    net_pnl = net_pnl - $2,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(session_id)
+            .bind(won)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to take back session winnings")?;
+        }
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+        /// Lamports won by the sessions' settled deploys in a round
+    pub async fn get_round_winnings(&self, session_ids: &[Uuid], round_id: i64) -> Result<i64> {
+        let won = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COALESCE(SUM(actual_reward), 0)::BIGINT FROM transactions
+            WHERE session_id = ANY($1) AND round_id = $2 AND status = 'won'
+            "#,
+        )
+        .bind(session_ids)
+        .bind(round_id)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to fetch round winnings")?;
+        
+        Ok(won)
+    }
+    
+    /// Get transactions for wallet
+    pub async fn get_transactions(
+        &self,
+        wallet: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Transaction>> {
+        let transactions = sqlx::query_as::<_, Transaction>(
+            r#"
+            SELECT * FROM transactions
+            WHERE user_wallet = $1
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(wallet)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch transactions")?;
+        
+        Ok(transactions)
+    }
+    
+    // =========================================================================
+    // AI Decision Audit
+    // =========================================================================
+    
+    /// Record an AI block selection
+    pub async fn record_ai_decision(&self, decision: &NewAiDecision) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        
+        sqlx::query(
+            r#"
+            INSERT INTO ai_decisions (
+                id, model, round_id, grid, prompt, raw_response, selection,
+                selected_blocks, skip, error, latency_ms, timed_out, used_instead,
+                experiment_id, experiment_arm, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, NOW())
+            "#,
+        )
+        .bind(id)
+        .bind(&decision.model)
+        .bind(decision.round_id)
+        .bind(&decision.grid)
+        .bind(&decision.prompt)
+        .bind(&decision.raw_response)
+        .bind(&decision.selection)
+        .bind(&decision.selected_blocks)
+        .bind(decision.skip)
+        .bind(&decision.error)
+        .bind(decision.latency_ms)
+        .bind(decision.timed_out)
+        .bind(&decision.used_instead)
+        .bind(decision.experiment_id)
+        .bind(&decision.experiment_arm)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record AI decision")?;
+        
+        debug!("Recorded AI decision {} for round {}", id, decision.round_id);
+        Ok(id)
+    }
+    
+    /// Mark a round's AI decisions won or lost; returns how many were updated
+    pub async fn resolve_ai_decisions(&self, round_id: i64, winning_square: i16) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE ai_decisions SET
+                winning_square = $2,
+                won = CASE
+                    WHEN skip OR error IS NOT NULL THEN NULL
+                    ELSE $2 = ANY(selected_blocks)
+                END,
+                resolved_at = NOW()
+            WHERE round_id = $1 AND resolved_at IS NULL
+            "#,
+        )
+        .bind(round_id)
+        .bind(winning_square)
+        .execute(&self.pool)
+        .await
+        .context("Failed to resolve AI decisions")?;
+        
+        Ok(result.rows_affected())
+    }
+    
+    /// Get AI decisions, newest first, optionally for one model
+    pub async fn get_ai_decisions(
+        &self,
+        model: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AiDecision>> {
+        let decisions = sqlx::query_as::<_, AiDecision>(
+            r#"
+            SELECT * FROM ai_decisions
+            WHERE ($1::VARCHAR IS NULL OR model = $1)
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(model)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch AI decisions")?;
+        
+        Ok(decisions)
+    }
+    
+    /// Win rate and latency per model
+    pub async fn get_ai_accuracy(&self) -> Result<Vec<AiModelAccuracy>> {
+        let accuracy = sqlx::query_as::<_, AiModelAccuracy>(
+            r#"
+            SELECT
+                model,
+                COUNT(*) AS decisions,
+                COUNT(won) AS resolved,
+                COUNT(*) FILTER (WHERE won) AS wins,
+                COUNT(*) FILTER (WHERE skip) AS skips,
+                COUNT(error) AS errors,
+                COUNT(*) FILTER (WHERE timed_out) AS timeouts,
+                COUNT(*) FILTER (WHERE won)::FLOAT8 / NULLIF(COUNT(won), 0) AS win_rate,
+                (AVG(cardinality(selected_blocks)) FILTER (WHERE won IS NOT NULL))::FLOAT8 / 25.0
+                    AS expected_win_rate,
+                AVG(latency_ms)::FLOAT8 AS avg_latency_ms
+            FROM ai_decisions
+            GROUP BY model
+            ORDER BY decisions DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch AI accuracy")?;
+        
+        Ok(accuracy)
+    }
+    
+    /// Record a finished round's winning square and final per-square stakes
+    pub async fn record_round_outcome(
+        &self,
+        round_id: i64,
+        winning_square: i16,
+        final_deployed: &[i64],
+        final_miners: &[i64],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO round_outcomes (round_id, winning_square, final_deployed, final_miners, resolved_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (round_id) DO NOTHING
+            "#,
+        )
+        .bind(round_id)
+        .bind(winning_square)
+        .bind(final_deployed)
+        .bind(final_miners)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record round outcome")?;
+        
+        Ok(())
+    }
+    
+    /// Distinct AI decision snapshots of finished rounds, with final stakes
+    pub async fn get_training_rows(&self) -> Result<Vec<TrainingRow>> {
+        let rows = sqlx::query_as::<_, TrainingRow>(
+            r#"
+            SELECT DISTINCT d.grid, r.final_deployed
+            FROM ai_decisions d
+            JOIN round_outcomes r ON r.round_id = d.round_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch training rows")?;
+        
+        Ok(rows)
+    }
+    
+    // =========================================================================
+    // Experiments
+    // =========================================================================
+    
+    /// Save a new experiment
+    pub async fn create_experiment(
+        &self,
+        id: Uuid,
+        name: &str,
+        assignment: &serde_json::Value,
+        arms: &serde_json::Value,
+    ) -> Result<ExperimentRecord> {
+        let record = sqlx::query_as::<_, ExperimentRecord>(
+            r#"
+            INSERT INTO experiments (id, name, assignment, arms, status, created_at)
+            VALUES ($1, $2, $3, $4, 'active', NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(name)
+        .bind(assignment)
+        .bind(arms)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to create experiment")?;
+        
+        info!("Created experiment {} ({})", name, id);
+        Ok(record)
+    }
+    
+    /// Get an experiment by id
+    pub async fn get_experiment(&self, id: Uuid) -> Result<Option<ExperimentRecord>> {
+        let record = sqlx::query_as::<_, ExperimentRecord>("SELECT * FROM experiments WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch experiment")?;
+        
+        Ok(record)
+    }
+    
+    /// List experiments, newest first
+    pub async fn list_experiments(&self) -> Result<Vec<ExperimentRecord>> {
+        let records = sqlx::query_as::<_, ExperimentRecord>("SELECT * FROM experiments ORDER BY created_at DESC")
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to list experiments")?;
+        
+        Ok(records)
+    }
+    
+    /// Stop an experiment (sessions already running keep their arms)
+    pub async fn end_experiment(&self, id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE experiments SET status = 'ended', ended_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to end experiment")?;
+        
+        Ok(())
+    }
+    
+    /// Deploys tagged with an experiment's arms
+    pub async fn get_experiment_transactions(&self, id: Uuid) -> Result<Vec<ExperimentTransaction>> {
+        let rows = sqlx::query_as::<_, ExperimentTransaction>(
+            r#"
+            SELECT experiment_arm, round_id, status, deploy_amount, tip_amount, actual_reward,
+                   COALESCE(squares, '{}') AS squares
+            FROM transactions
+            WHERE experiment_id = $1 AND experiment_arm IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch experiment transactions")?;
+        
+        Ok(rows)
+    }
+    
+    // =========================================================================
+    // Session Script Operations
+    // =========================================================================
+    
+    /// Save a session's script, bumping its version
+    pub async fn save_session_script(&self, session_id: Uuid, source: &str) -> Result<SessionScript> {
+        let script = sqlx::query_as::<_, SessionScript>(
+            r#"
+            INSERT INTO session_scripts (session_id, source, version, updated_at)
+            VALUES ($1, $2, 1, NOW())
+            ON CONFLICT (session_id) DO UPDATE SET
+                source = EXCLUDED.source,
+                version = session_scripts.version + 1,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(session_id)
+        .bind(source)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to save session script")?;
+        
+        Ok(script)
+    }
+    
+    /// Get a session's script
+    pub async fn get_session_script(&self, session_id: Uuid) -> Result<Option<SessionScript>> {
+        let script = sqlx::query_as::<_, SessionScript>("SELECT * FROM session_scripts WHERE session_id = $1")
+            .bind(session_id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch session script")?;
+        
+        Ok(script)
+    }
+    
+    /// Remove a session's script
+    pub async fn delete_session_script(&self, session_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM session_scripts WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete session script")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // =========================================================================
+    // Round Skip Operations
+    // =========================================================================
+    
+    /// Record a skipped round with the motherlode at decision time and the
+    /// experiment arm (id, name) the round was assigned to
+    pub async fn record_round_skip(
+        &self,
+        session_id: Uuid,
+        wallet: &str,
+        round_id: i64,
+        reason: &str,
+        motherlode: i64,
+        experiment: Option<(Uuid, &str)>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO round_skips (
+                id, session_id, user_wallet, round_id, reason, motherlode,
+                experiment_id, experiment_arm, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(session_id)
+        .bind(wallet)
+        .bind(round_id)
+        .bind(reason)
+        .bind(motherlode)
+        .bind(experiment.map(|(id, _)| id))
+        .bind(experiment.map(|(_, arm)| arm))
+        .execute(&self.pool)
+        .await
+        .context("Failed to record round skip")?;
+        
+        Ok(())
+    }
+    
+    /// Get a session's skipped rounds, newest first
+    pub async fn get_round_skips(&self, session_id: Uuid, limit: i64) -> Result<Vec<RoundSkip>> {
+        let skips = sqlx::query_as::<_, RoundSkip>(
+            "SELECT * FROM round_skips WHERE session_id = $1 ORDER BY round_id DESC LIMIT $2",
+        )
+        .bind(session_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch round skips")?;
+        
+        Ok(skips)
+    }
+    
+    // =========================================================================
+    // Fleet Operations
+    // =========================================================================
+    
+    /// Create a fleet led by `lead_wallet`
+    pub async fn create_fleet(&self, lead_wallet: &str, split: &str) -> Result<FleetRecord> {
+        let fleet = sqlx::query_as::<_, FleetRecord>(
+            r#"
+            INSERT INTO fleets (id, lead_wallet, split, is_active, created_at)
+            VALUES ($1, $2, $3, true, NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(lead_wallet)
+        .bind(split)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to create fleet")?;
+        
+        info!("Created fleet {} led by {}", fleet.id, lead_wallet);
+        Ok(fleet)
+    }
+    
+    /// Link a member wallet's session to its fleet
+    pub async fn add_fleet_session(&self, fleet_id: Uuid, session_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE sessions SET fleet_id = $1 WHERE id = $2")
+            .bind(fleet_id)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to link session to fleet")?;
+        
+        Ok(())
+    }
+    
+    /// Active fleet led by a wallet
+    pub async fn get_active_fleet(&self, lead_wallet: &str) -> Result<Option<FleetRecord>> {
+        let fleet = sqlx::query_as::<_, FleetRecord>(
+            "SELECT * FROM fleets WHERE lead_wallet = $1 AND is_active = true ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(lead_wallet)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch fleet")?;
+        
+        Ok(fleet)
+    }
+    
+    /// A fleet's member sessions, lead first
+    pub async fn get_fleet_sessions(&self, fleet_id: Uuid) -> Result<Vec<Session>> {
+        let sessions = sqlx::query_as::<_, Session>(
+            "SELECT * FROM sessions WHERE fleet_id = $1 ORDER BY created_at ASC",
+        )
+        .bind(fleet_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch fleet sessions")?;
+        
+        Ok(sessions)
+    }
+    
+    /// End a fleet and all of its member sessions
+    pub async fn end_fleet(&self, fleet_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE fleets SET is_active = false, ended_at = NOW() WHERE id = $1")
+            .bind(fleet_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to end fleet")?;
+        sqlx::query("UPDATE sessions SET is_active = false, updated_at = NOW() WHERE fleet_id = $1 AND is_active = true")
+            .bind(fleet_id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to end fleet sessions")?;
+        
+        info!("Ended fleet {}", fleet_id);
+        Ok(())
+    }
+    
+    // =========================================================================
+    // Schedule Operations
     // =========================================================================
     
-    /// Create a new mining session
-    /// Amounts are in lamports (1 SOL = 1_000_000_000 lamports)
-    pub async fn create_session(
+    /// Store a new schedule
+    pub async fn create_schedule(
         &self,
         wallet: &str,
-        strategy: Strategy,
-        max_tip: i64,
-        deploy_amount: i64,
-        budget: i64,
-    ) -> Result<Session> {
-        let strategy_str = match strategy {
-            Strategy::BestEv => "best_ev",
-            Strategy::Conservative => "conservative",
-            Strategy::Aggressive => "aggressive",
-        };
-        
-        let session = sqlx::query_as::<_, Session>(
+        spec: &serde_json::Value,
+        session: &serde_json::Value,
+        max_rounds: Option<i64>,
+        until_at: Option<DateTime<Utc>>,
+        enabled: bool,
+    ) -> Result<ScheduleRecord> {
+        let schedule = sqlx::query_as::<_, ScheduleRecord>(
             r#"
-            INSERT INTO sessions (
-                id, user_wallet, strategy, max_tip, deploy_amount, budget,
-                rounds_played, rounds_skipped, total_deployed, total_tips,
-                total_won, net_pnl, is_active, created_at, updated_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, 0, 0, 0, 0, 0, 0, true, NOW(), NOW())
+            INSERT INTO schedules (id, wallet, spec, session, max_rounds, until_at, enabled, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
             RETURNING *
             "#,
         )
         .bind(Uuid::new_v4())
         .bind(wallet)
-        .bind(strategy_str)
-        .bind(max_tip)
-        .bind(deploy_amount)
-        .bind(budget)
+        .bind(spec)
+        .bind(session)
+        .bind(max_rounds)
+        .bind(until_at)
+        .bind(enabled)
         .fetch_one(&self.pool)
         .await
-        .context("Failed to create session")?;
+        .context("Failed to create schedule")?;
         
-        info!("Created session {} for wallet {}", session.id, wallet);
-        Ok(session)
+        info!("Created schedule {} for {}", schedule.id, wallet);
+        Ok(schedule)
     }
     
-    /// End a mining session
-    pub async fn end_session(&self, wallet: &str) -> Result<()> {
-        sqlx::query(
-            "UPDATE sessions SET is_active = false, updated_at = NOW() WHERE user_wallet = $1 AND is_active = true"
+    /// List schedules, optionally for one wallet
+    pub async fn list_schedules(&self, wallet: Option<&str>) -> Result<Vec<ScheduleRecord>> {
+        let schedules = sqlx::query_as::<_, ScheduleRecord>(
+            "SELECT * FROM schedules WHERE $1::TEXT IS NULL OR wallet = $1 ORDER BY created_at ASC",
         )
         .bind(wallet)
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await
-        .context("Failed to end session")?;
+        .context("Failed to list schedules")?;
         
-        Ok(())
+        Ok(schedules)
     }
     
-    /// Get active session for wallet
-    pub async fn get_active_session(&self, wallet: &str) -> Result<Option<Session>> {
-        let session = sqlx::query_as::<_, Session>(
-            "SELECT * FROM sessions WHERE user_wallet = $1 AND is_active = true ORDER BY created_at DESC LIMIT 1"
+    /// Get a schedule by ID
+    pub async fn get_schedule(&self, id: Uuid) -> Result<Option<ScheduleRecord>> {
+        let schedule = sqlx::query_as::<_, ScheduleRecord>("SELECT * FROM schedules WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch schedule")?;
+        
+        Ok(schedule)
+    }
+    
+    /// Replace a schedule's spec, session request and limits
+    pub async fn update_schedule(
+        &self,
+        id: Uuid,
+        spec: &serde_json::Value,
+        session: &serde_json::Value,
+        max_rounds: Option<i64>,
+        until_at: Option<DateTime<Utc>>,
+        enabled: bool,
+    ) -> Result<Option<ScheduleRecord>> {
+        let schedule = sqlx::query_as::<_, ScheduleRecord>(
+            r#"
+            UPDATE schedules
+            SET spec = $2, session = $3, max_rounds = $4, until_at = $5, enabled = $6, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
         )
-        .bind(wallet)
+        .bind(id)
+        .bind(spec)
+        .bind(session)
+        .bind(max_rounds)
+        .bind(until_at)
+        .bind(enabled)
         .fetch_optional(&self.pool)
         .await
-        .context("Failed to fetch session")?;
+        .context("Failed to update schedule")?;
         
-        Ok(session)
+        Ok(schedule)
     }
     
-    /// Update session statistics (amounts in lamports)
-    pub async fn update_session_stats(
-        &self,
-        session_id: Uuid,
-        deployed: i64,
-        tip: i64,
-        reward: Option<i64>,
-        is_skip: bool,
-    ) -> Result<()> {
-        let won = reward.unwrap_or(0);
-        
-        if is_skip {
-            sqlx::query(
-                r#"
-                UPDATE sessions SET
-                    rounds_skipped = rounds_skipped + 1,
-                    updated_at = NOW()
-                WHERE id = $1
-                "#,
-            )
-            .bind(session_id)
-            .execute(&self.pool)
-            .await?;
-        } else {
-            let net = won - deployed - tip;
-            sqlx::query(
-                r#"
-                UPDATE sessions SET
-                    rounds_played = rounds_played + 1,
-                    total_deployed = total_deployed + $2,
-                    total_tips = total_tips + $3,
-                    total_won = total_won + $4,
-                    net_pnl = net_pnl + $5,
-                    updated_at = NOW()
-                WHERE id = $1
-                "#,
-            )
-            .bind(session_id)
-            .bind(deployed)
-            .bind(tip)
-            .bind(won)
-            .bind(net)
+    /// Delete a schedule; returns whether it existed
+    pub async fn delete_schedule(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM schedules WHERE id = $1")
+            .bind(id)
             .execute(&self.pool)
-            .await?;
-        }
+            .await
+            .context("Failed to delete schedule")?;
         
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
     
-    /// Get session stats
-    pub async fn get_session_stats(&self, session_id: Uuid) -> Result<SessionStats> {
-        let session = sqlx::query_as::<_, Session>(
-            "SELECT * FROM sessions WHERE id = $1"
-        )
-        .bind(session_id)
-        .fetch_one(&self.pool)
-        .await
-        .context("Session not found")?;
-        
-        let rounds_won = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM transactions WHERE session_id = $1 AND status = 'won'"
-        )
-        .bind(session_id)
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(0);
-        
-        let rounds_lost = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM transactions WHERE session_id = $1 AND status = 'lost'"
+    /// Record a schedule's run in progress (None once it ends); `started`
+    /// stamps the start time
+    pub async fn set_schedule_run(&self, id: Uuid, session_id: Option<Uuid>, started: bool) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE schedules
+            SET session_id = $2,
+                last_started_at = CASE WHEN $3 THEN NOW() ELSE last_started_at END,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
         )
+        .bind(id)
         .bind(session_id)
-        .fetch_one(&self.pool)
+        .bind(started)
+        .execute(&self.pool)
         .await
-        .unwrap_or(0);
+        .context("Failed to record schedule run")?;
         
-        let total_rounds = rounds_won + rounds_lost;
-        let win_rate = if total_rounds > 0 {
-            rounds_won as f64 / total_rounds as f64
-        } else {
-            0.0
-        };
+        Ok(())
+    }
+    
+    /// Enable or disable a schedule
+    pub async fn set_schedule_enabled(&self, id: Uuid, enabled: bool) -> Result<()> {
+        sqlx::query("UPDATE schedules SET enabled = $2, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(enabled)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update schedule")?;
         
-        Ok(SessionStats {
-            rounds_played: session.rounds_played,
-            rounds_skipped: session.rounds_skipped,
-            rounds_won,
-            rounds_lost,
-            total_deployed: session.total_deployed,
-            total_tips: session.total_tips,
-            total_won: session.total_won,
-            net_pnl: session.net_pnl,
-            win_rate,
-        })
+        Ok(())
     }
     
     // =========================================================================
-    // Transaction Operations
+    // Risk Operations
     // =========================================================================
     
-    /// Record a new transaction (amounts in lamports)
-    pub async fn record_transaction(
-        &self,
-        wallet: &str,
-        session_id: Option<Uuid>,
-        round_id: i64,
-        block_index: i16,
-        deploy_amount: i64,
-        tip_amount: i64,
-        expected_ev: i64,
-        strategy: &str,
-    ) -> Result<Transaction> {
-        let tx = sqlx::query_as::<_, Transaction>(
+    /// Get the risk state of a scope (`global` or a wallet)
+    pub async fn get_risk_state(&self, scope: &str) -> Result<Option<RiskState>> {
+        let state = sqlx::query_as::<_, RiskState>("SELECT * FROM risk_state WHERE scope = $1")
+            .bind(scope)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch risk state")?;
+        
+        Ok(state)
+    }
+    
+    /// Engage or clear the global kill switch; clearing restarts drawdown measurement
+    pub async fn set_kill_switch(&self, engaged: bool, reason: Option<&str>) -> Result<RiskState> {
+        let state = sqlx::query_as::<_, RiskState>(
             r#"
-            INSERT INTO transactions (
-                id, user_wallet, session_id, round_id, block_index,
-                deploy_amount, tip_amount, expected_ev, status, strategy,
-                created_at, updated_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'pending', $9, NOW(), NOW())
+            INSERT INTO risk_state (scope, blocked, reason, baseline_at, updated_at)
+            VALUES ('global', $1, $2, NOW(), NOW())
+            ON CONFLICT (scope) DO UPDATE SET
+                blocked = EXCLUDED.blocked,
+                reason = EXCLUDED.reason,
+                baseline_at = CASE WHEN EXCLUDED.blocked THEN risk_state.baseline_at ELSE NOW() END,
+                updated_at = NOW()
             RETURNING *
             "#,
         )
-        .bind(Uuid::new_v4())
-        .bind(wallet)
-        .bind(session_id)
-        .bind(round_id)
-        .bind(block_index)
-        .bind(deploy_amount)
-        .bind(tip_amount)
-        .bind(expected_ev)
-        .bind(strategy)
+        .bind(engaged)
+        .bind(reason)
         .fetch_one(&self.pool)
         .await
-        .context("Failed to record transaction")?;
+        .context("Failed to set kill switch")?;
         
-        debug!("Recorded transaction {} for round {}", tx.id, round_id);
-        Ok(tx)
+        Ok(state)
     }
     
-    /// Update transaction status
-    pub async fn update_transaction_status(
-        &self,
-        tx_id: Uuid,
-        status: TxStatus,
-        signature: Option<&str>,
-        reward: Option<i64>,
-    ) -> Result<()> {
+    /// Start a wallet cooldown that lasts until `until_round`
+    pub async fn set_cooldown(&self, wallet: &str, until_round: i64, reason: &str) -> Result<()> {
         sqlx::query(
             r#"
-            UPDATE transactions SET
-                status = $2,
-                tx_signature = COALESCE($3, tx_signature),
-                actual_reward = $4,
+            INSERT INTO risk_state (scope, blocked, until_round, reason, baseline_at, updated_at)
+            VALUES ($1, TRUE, $2, $3, NOW(), NOW())
+            ON CONFLICT (scope) DO UPDATE SET
+                blocked = TRUE,
+                until_round = EXCLUDED.until_round,
+                reason = EXCLUDED.reason,
                 updated_at = NOW()
-            WHERE id = $1
             "#,
         )
-        .bind(tx_id)
-        .bind(status.as_str())
-        .bind(signature)
-        .bind(reward)
+        .bind(wallet)
+        .bind(until_round)
+        .bind(reason)
         .execute(&self.pool)
         .await
-        .context("Failed to update transaction")?;
+        .context("Failed to set cooldown")?;
         
         Ok(())
     }
     
-    /// Get transactions for wallet
-    pub async fn get_transactions(
-        &self,
-        wallet: &str,
-        limit: i64,
-        offset: i64,
-    ) -> Result<Vec<Transaction>> {
-        let transactions = sqlx::query_as::<_, Transaction>(
+    /// Net SOL lost by a wallet's settled deploys since `since` (positive = loss)
+    pub async fn get_net_loss_since(&self, wallet: &str, since: DateTime<Utc>) -> Result<i64> {
+        let loss: i64 = sqlx::query_scalar(
             r#"
-            SELECT * FROM transactions
-            WHERE user_wallet = $1
-            ORDER BY created_at DESC
-            LIMIT $2 OFFSET $3
+            SELECT COALESCE(SUM(
+                deploy_amount * COALESCE(array_length(squares, 1), 1) + tip_amount - COALESCE(actual_reward, 0)
+            ), 0)::BIGINT
+            FROM transactions
+            WHERE user_wallet = $1 AND status IN ('won', 'lost') AND created_at >= $2
             "#,
         )
         .bind(wallet)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
+        .bind(since)
+        .fetch_one(&self.pool)
         .await
-        .context("Failed to fetch transactions")?;
+        .context("Failed to fetch net loss")?;
         
-        Ok(transactions)
+        Ok(loss)
+    }
+    
+    /// Consecutive losing rounds of a wallet from `from_round` on, since its last win
+    pub async fn get_wallet_loss_streak(&self, wallet: &str, from_round: i64) -> Result<i64> {
+        let streak: i64 = sqlx::query_scalar(
+            r#"
+            WITH rounds AS (
+                SELECT round_id, BOOL_OR(status = 'won') AS won
+                FROM transactions
+                WHERE user_wallet = $1 AND status IN ('won', 'lost') AND round_id >= $2
+                GROUP BY round_id
+            )
+            SELECT COUNT(*) FROM rounds
+            WHERE NOT won AND round_id > COALESCE((SELECT MAX(round_id) FROM rounds WHERE won), -1)
+            "#,
+        )
+        .bind(wallet)
+        .bind(from_round)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to fetch wallet loss streak")?;
+        
+        Ok(streak)
+    }
+    
+    /// Drawdown of all wallets' combined settled P&L since `since`: the peak
+    /// cumulative P&L (at least 0) minus the current one
+    pub async fn get_drawdown_since(&self, since: DateTime<Utc>) -> Result<i64> {
+        let drawdown: i64 = sqlx::query_scalar(
+            r#"
+            WITH pnl AS (
+                SELECT round_id, SUM(
+                    COALESCE(actual_reward, 0) - deploy_amount * COALESCE(array_length(squares, 1), 1) - tip_amount
+                ) AS net
+                FROM transactions
+                WHERE status IN ('won', 'lost') AND created_at >= $1
+                GROUP BY round_id
+            ), running AS (
+                SELECT round_id, SUM(net) OVER (ORDER BY round_id) AS total FROM pnl
+            )
+            SELECT COALESCE(
+                GREATEST(MAX(total), 0) - (SELECT total FROM running ORDER BY round_id DESC LIMIT 1),
+                0
+            )::BIGINT
+            FROM running
+            "#,
+        )
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to fetch drawdown")?;
+        
+        Ok(drawdown)
     }
     
     // =========================================================================
@@ -686,6 +1948,33 @@ impl Database {
         Ok(wallets)
     }
     
+    /// Replace a wallet's tags
+    pub async fn set_wallet_tags(&self, wallet_address: &str, tags: &[String]) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE wallets SET tags = $2 WHERE wallet_address = $1 AND is_active = true",
+        )
+        .bind(wallet_address)
+        .bind(tags)
+        .execute(&self.pool)
+        .await
+        .context("Failed to tag wallet")?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Active wallets carrying a tag, oldest first
+    pub async fn get_wallets_with_tag(&self, tag: &str) -> Result<Vec<String>> {
+        let wallets: Vec<String> = sqlx::query_scalar(
+            "SELECT wallet_address FROM wallets WHERE is_active = true AND $1 = ANY(tags) ORDER BY created_at ASC",
+        )
+        .bind(tag)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list tagged wallets")?;
+        
+        Ok(wallets)
+    }
+    
     /// Update last used timestamp
     pub async fn touch_wallet(&self, wallet_address: &str) -> Result<()> {
         sqlx::query(
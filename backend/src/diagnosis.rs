@@ -0,0 +1,317 @@
+//! Failed Transaction Diagnosis
+//!
+//! Fetches a failed transaction's logs, decodes ORE program errors and
+//! classifies why it failed, so the engine can pick a remediation instead of
+//! logging a raw `TransactionError` and moving on. Causes come from the error
+//! codes and the miner's on-chain state; logs are kept for reference only.
+
+use serde::Serialize;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::InstructionError,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+use tracing::{debug, warn};
+
+use crate::ore::{OreClient, OreProgramError, ORE_PROGRAM_ID};
+
+/// Why a transaction failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailureCause {
+    /// Landed at or after the round's end slot
+    RoundEnded,
+    /// Miner still holds an un-checkpointed previous round
+    NotCheckpointed,
+    /// Automation account can't cover the deploy
+    InsufficientAutomationBalance,
+    /// Wallet can't pay fees or rent
+    InsufficientFunds,
+    BlockhashExpired,
+    ComputeExceeded,
+    /// The RPC refused the transaction, so it never went out
+    SendFailed { message: String },
+    /// ORE program error without a more specific cause
+    Ore { error: OreProgramError },
+    Unknown { message: String },
+}
+
+impl FailureCause {
+    /// Short label stored on the transaction record
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::RoundEnded => "round_ended",
+            Self::NotCheckpointed => "not_checkpointed",
+            Self::InsufficientAutomationBalance => "insufficient_automation_balance",
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::BlockhashExpired => "blockhash_expired",
+            Self::ComputeExceeded => "compute_exceeded",
+            Self::SendFailed { .. } => "send_failed",
+            Self::Ore { .. } => "ore_error",
+            Self::Unknown { .. } => "unknown",
+        }
+    }
+
+    /// What the engine should do about it
+    pub fn remediation(&self) -> Remediation {
+        match self {
+            Self::NotCheckpointed => Remediation::CheckpointThenRetry,
+            Self::InsufficientAutomationBalance => Remediation::FundAutomationThenRetry,
+            Self::BlockhashExpired | Self::SendFailed { .. } => Remediation::RetryWithFreshBlockhash,
+            _ => Remediation::None,
+        }
+    }
+}
+
+/// Automatic follow-up for a failed deploy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Remediation {
+    None,
+    CheckpointThenRetry,
+    FundAutomationThenRetry,
+    RetryWithFreshBlockhash,
+}
+
+/// Diagnosis of a failed transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnosis {
+    pub cause: FailureCause,
+    pub remediation: Remediation,
+    /// Index of the failing instruction
+    pub instruction_index: Option<u8>,
+    /// Decoded ORE error, if the failing instruction was ORE's
+    pub ore_error: Option<OreProgramError>,
+    pub raw_error: Option<String>,
+    /// Slot the transaction landed in
+    pub slot: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+impl Diagnosis {
+    /// Diagnosis of a transaction the RPC refused to take
+    pub fn send_failed(message: String) -> Self {
+        let cause = FailureCause::SendFailed { message: message.clone() };
+        Self {
+            remediation: cause.remediation(),
+            cause,
+            instruction_index: None,
+            ore_error: None,
+            raw_error: Some(message),
+            slot: None,
+            logs: Vec::new(),
+        }
+    }
+}
+
+/// The failing wallet's miner and automation state, read after the failure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MinerState {
+    /// The miner still holds an un-checkpointed round
+    pub needs_checkpoint: bool,
+    /// The automation account holds less than the deploy needs
+    pub automation_short: bool,
+}
+
+/// Classify a failure from its error, landing slot and the miner's state
+pub fn classify(
+    tx: &Transaction,
+    error: Option<&TransactionError>,
+    landed_slot: Option<u64>,
+    end_slot: u64,
+    miner: MinerState,
+) -> FailureCause {
+    let Some(error) = error else {
+        // Never landed - the only way that ends is the blockhash expiring
+        return FailureCause::BlockhashExpired;
+    };
+
+    match error {
+        TransactionError::BlockhashNotFound => return FailureCause::BlockhashExpired,
+        TransactionError::InsufficientFundsForFee
+        | TransactionError::InsufficientFundsForRent { .. } => return FailureCause::InsufficientFunds,
+        _ => {}
+    }
+
+    if landed_slot.is_some_and(|slot| slot >= end_slot) {
+        return FailureCause::RoundEnded;
+    }
+
+    let TransactionError::InstructionError(_, ix_error) = error else {
+        return FailureCause::Unknown { message: format!("{:?}", error) };
+    };
+
+    if matches!(ix_error, InstructionError::ComputationalBudgetExceeded) {
+        return FailureCause::ComputeExceeded;
+    }
+    if !failing_program_is_ore(tx, error) {
+        return FailureCause::Unknown { message: format!("{:?}", error) };
+    }
+
+    // Named ORE errors say what went wrong; anything else is judged from the miner's state
+    match OreProgramError::from_transaction_error(tx, error) {
+        Some(OreProgramError::Unknown(_)) | None => {}
+        Some(error) => return FailureCause::Ore { error },
+    }
+    if matches!(ix_error, InstructionError::InsufficientFunds) || miner.automation_short {
+        return FailureCause::InsufficientAutomationBalance;
+    }
+    if miner.needs_checkpoint {
+        return FailureCause::NotCheckpointed;
+    }
+    match OreProgramError::from_transaction_error(tx, error) {
+        Some(error) => FailureCause::Ore { error },
+        None => FailureCause::Unknown { message: format!("{:?}", error) },
+    }
+}
+
+fn failing_program_is_ore(tx: &Transaction, error: &TransactionError) -> bool {
+    let TransactionError::InstructionError(index, _) = error else {
+        return false;
+    };
+    tx.message.instructions.get(*index as usize)
+        .and_then(|ix| tx.message.account_keys.get(ix.program_id_index as usize))
+        .is_some_and(|program| *program == ORE_PROGRAM_ID)
+}
+
+/// Fetches failed transactions and diagnoses them
+#[derive(Clone)]
+pub struct Diagnoser {
+    ore_client: OreClient,
+}
+
+impl Diagnoser {
+    /// Create a new diagnoser
+    pub fn new(ore_client: OreClient) -> Self {
+        Self { ore_client }
+    }
+
+    /// Diagnose a failed transaction
+    ///
+    /// `error` is `None` when the transaction never landed. The landing slot
+    /// and logs are fetched from the chain when it did, along with the
+    /// miner's state; `deploy_total` is what a failed deploy needed from the
+    /// automation account.
+    pub async fn diagnose(
+        &self,
+        tx: &Transaction,
+        error: Option<&TransactionError>,
+        end_slot: u64,
+        deploy_total: Option<u64>,
+    ) -> Diagnosis {
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+        let ((slot, logs), miner) = match error {
+            Some(_) => {
+                let (landed, miner) = tokio::join!(self.fetch_logs(&signature), self.miner_state(tx, deploy_total));
+                let landed = landed.unwrap_or_else(|e| {
+                    warn!("Could not fetch logs for {}: {}", signature, e);
+                    (None, Vec::new())
+                });
+                (landed, miner)
+            }
+            None => ((None, Vec::new()), MinerState::default()),
+        };
+
+        let cause = classify(tx, error, slot, end_slot, miner);
+        debug!("Diagnosed {} as {:?}", signature, cause);
+
+        Diagnosis {
+            remediation: cause.remediation(),
+            cause,
+            instruction_index: match error {
+                Some(TransactionError::InstructionError(index, _)) => Some(*index),
+                _ => None,
+            },
+            ore_error: error.and_then(|e| OreProgramError::from_transaction_error(tx, e)),
+            raw_error: error.map(|e| format!("{:?}", e)),
+            slot,
+            logs,
+        }
+    }
+
+    /// Checkpoint and automation state of the transaction's fee payer
+    async fn miner_state(&self, tx: &Transaction, deploy_total: Option<u64>) -> MinerState {
+        let Some(wallet) = tx.message.account_keys.first() else {
+            return MinerState::default();
+        };
+        let (board, miner, automation) = tokio::join!(
+            self.ore_client.get_board_state(),
+            self.ore_client.get_miner_data(wallet),
+            async {
+                match deploy_total {
+                    Some(_) => self.ore_client.get_automation_balance(wallet).await.ok(),
+                    None => None,
+                }
+            },
+        );
+        let needs_checkpoint = match (board, miner) {
+            (Ok(board), Ok(Some(miner))) => miner.needs_checkpoint(board.round_id),
+            _ => false,
+        };
+        MinerState {
+            needs_checkpoint,
+            automation_short: matches!((automation, deploy_total), (Some(balance), Some(total)) if balance < total),
+        }
+    }
+
+    async fn fetch_logs(&self, signature: &Signature) -> anyhow::Result<(Option<u64>, Vec<String>)> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let confirmed = self.ore_client.rpc()
+            .get_transaction_with_config(signature, config)
+            .await?;
+
+        let logs = match confirmed.transaction.meta.map(|m| m.log_messages) {
+            Some(OptionSerializer::Some(logs)) => logs,
+            _ => Vec::new(),
+        };
+        Ok((Some(confirmed.slot), logs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+    fn ore_tx() -> Transaction {
+        let payer = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(ORE_PROGRAM_ID, &[6], vec![]);
+        Transaction::new_with_payer(&[ix], Some(&payer))
+    }
+
+    #[test]
+    fn test_classify_causes() {
+        let tx = ore_tx();
+        let code = ore_api::error::OreError::AmountTooSmall as u32;
+        let custom = TransactionError::InstructionError(0, InstructionError::Custom(code));
+        let failed = TransactionError::InstructionError(0, InstructionError::InvalidAccountData);
+        let stale = MinerState { needs_checkpoint: true, ..Default::default() };
+
+        assert_eq!(classify(&tx, None, None, 100, stale), FailureCause::BlockhashExpired);
+        assert_eq!(classify(&tx, Some(&custom), Some(100), 100, stale), FailureCause::RoundEnded);
+        assert_eq!(classify(&tx, Some(&failed), Some(90), 100, stale), FailureCause::NotCheckpointed);
+        assert_eq!(
+            classify(&tx, Some(&failed), Some(90), 100, MinerState { automation_short: true, ..Default::default() }),
+            FailureCause::InsufficientAutomationBalance
+        );
+        // A named ORE error wins over the miner's state
+        assert_eq!(
+            classify(&tx, Some(&custom), Some(90), 100, stale),
+            FailureCause::Ore { error: OreProgramError::AmountTooSmall }
+        );
+    }
+
+    #[test]
+    fn test_remediation() {
+        assert_eq!(FailureCause::NotCheckpointed.remediation(), Remediation::CheckpointThenRetry);
+        assert_eq!(FailureCause::RoundEnded.remediation(), Remediation::None);
+        assert_eq!(Diagnosis::send_failed("rpc down".to_string()).remediation, Remediation::RetryWithFreshBlockhash);
+    }
+}
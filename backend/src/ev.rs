@@ -0,0 +1,260 @@
+//! Expected Value
+//!
+//! The ORE v3 payoff model behind every EV number. When a round resets, one
+//! of the 25 squares wins at random. Stake on the winning square is returned
+//! (less the admin fee), and the stake on the other 24 squares, less the admin
+//! fee and the vault cut, is split pro rata among the winning square's miners.
+//! They also share the round's ORE emission and, with 1-in-625 odds, the
+//! motherlode. Claims pay a 10% fee. Amounts are fixed-point integers
+//! (lamports, ORE grains); only the win probability is a float.
+
+use serde::{Deserialize, Serialize};
+
+use crate::claims::CLAIM_FEE_PERCENT;
+
+/// ORE has 11 decimals
+pub const ONE_ORE: u64 = 100_000_000_000;
+
+/// Basis-point denominator
+const BPS: u128 = 10_000;
+
+/// Protocol parameters of the payoff model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvParams {
+    /// Admin fee on all deployed SOL (basis points)
+    pub admin_fee_bps: u64,
+    /// Share of the losing squares' SOL sent to the vault (basis points)
+    pub vault_bps: u64,
+    /// Fee on claimed SOL and ORE (basis points)
+    pub claim_fee_bps: u64,
+    /// ORE minted to the winning square each round (grains)
+    pub ore_per_round: u64,
+    /// The motherlode pays out once in this many rounds
+    pub motherlode_odds: u64,
+    /// Lamports per ORE used to value ORE rewards (0 values ORE at nothing)
+    pub ore_price: u64,
+}
+
+impl Default for EvParams {
+    fn default() -> Self {
+        Self {
+            admin_fee_bps: 100,
+            vault_bps: 1_000,
+            claim_fee_bps: (CLAIM_FEE_PERCENT * BPS as f64).round() as u64,
+            ore_per_round: ONE_ORE,
+            motherlode_odds: 625,
+            ore_price: 0,
+        }
+    }
+}
+
+/// What a deploy claims if a given square wins
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Payout {
+    /// Lamports, after the claim fee
+    pub sol: u64,
+    /// Expected ORE grains (emission plus motherlode odds), after the claim fee
+    pub ore: u64,
+}
+
+/// Expected value of a deploy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DeployEv {
+    /// Lamports spent: the deploy on every square plus the tip
+    pub cost: u64,
+    /// Expected lamports claimed
+    pub expected_sol: u64,
+    /// Expected ORE grains claimed
+    pub expected_ore: u64,
+    /// Expected profit in lamports, with ORE valued at `ore_price`
+    pub ev: i64,
+    /// Chance that one of the deployed squares wins
+    pub win_probability: f64,
+}
+
+fn at(values: &[u64], index: usize) -> u128 {
+    values.get(index).copied().unwrap_or(0) as u128
+}
+
+fn after_fee(amount: u128, bps: u64) -> u128 {
+    amount * (BPS - (bps as u128).min(BPS)) / BPS
+}
+
+impl EvParams {
+    /// Lamports an amount of ORE grains is worth
+    pub fn ore_value(&self, grains: u64) -> u64 {
+        (grains as u128 * self.ore_price as u128 / ONE_ORE as u128) as u64
+    }
+
+    /// What's left of a claimed amount after the claim fee
+    pub fn after_claim_fee(&self, amount: u64) -> u64 {
+        after_fee(amount as u128, self.claim_fee_bps) as u64
+    }
+
+    /// Claim if `square` wins, given the current stakes and our per-square amounts
+    pub fn payout(&self, deployed: &[u64], motherlode: u64, amounts: &[u64], square: usize) -> Payout {
+        let stake = at(amounts, square);
+        if stake == 0 {
+            return Payout::default();
+        }
+        let total: u128 = (0..25).map(|i| at(deployed, i) + at(amounts, i)).sum();
+        let square_total = at(deployed, square) + stake;
+
+        let winnings = after_fee(after_fee(total - square_total, self.admin_fee_bps), self.vault_bps);
+        let sol = after_fee(stake, self.admin_fee_bps) + winnings * stake / square_total;
+        let ore_pool = self.ore_per_round as u128 + motherlode as u128 / self.motherlode_odds.max(1) as u128;
+        let ore = ore_pool * stake / square_total;
+
+        Payout {
+            sol: after_fee(sol, self.claim_fee_bps) as u64,
+            ore: after_fee(ore, self.claim_fee_bps) as u64,
+        }
+    }
+
+    /// EV of deploying `amounts[i]` lamports on each square i (0 = not deployed)
+    pub fn evaluate(&self, deployed: &[u64], motherlode: u64, amounts: &[u64], tip: u64) -> DeployEv {
+        let squares: Vec<usize> = (0..25).filter(|&i| at(amounts, i) > 0).collect();
+        let (sol, ore) = squares.iter().fold((0u128, 0u128), |(sol, ore), &i| {
+            let payout = self.payout(deployed, motherlode, amounts, i);
+            (sol + payout.sol as u128, ore + payout.ore as u128)
+        });
+        let expected_sol = (sol / 25) as u64;
+        let expected_ore = (ore / 25) as u64;
+        let cost = (0..25).map(|i| at(amounts, i)).sum::<u128>() as u64 + tip;
+        let ev = expected_sol as i128 + self.ore_value(expected_ore) as i128 - cost as i128;
+
+        DeployEv {
+            cost,
+            expected_sol,
+            expected_ore,
+            ev: ev.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+            win_probability: squares.len() as f64 / 25.0,
+        }
+    }
+
+    /// EV of deploying `amount` lamports on one square
+    pub fn single(&self, deployed: &[u64], motherlode: u64, square: usize, amount: u64, tip: u64) -> DeployEv {
+        let mut amounts = [0u64; 25];
+        if square < 25 {
+            amounts[square] = amount;
+        }
+        self.evaluate(deployed, motherlode, &amounts, tip)
+    }
+
+    /// EV of deploying `amount` lamports on each of `squares`
+    pub fn uniform(&self, deployed: &[u64], motherlode: u64, squares: &[u8], amount: u64, tip: u64) -> DeployEv {
+        let mut amounts = [0u64; 25];
+        for &square in squares.iter().filter(|&&s| s < 25) {
+            amounts[square as usize] = amount;
+        }
+        self.evaluate(deployed, motherlode, &amounts, tip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn no_fees() -> EvParams {
+        EvParams {
+            admin_fee_bps: 0,
+            vault_bps: 0,
+            claim_fee_bps: 0,
+            ore_per_round: 0,
+            motherlode_odds: 625,
+            ore_price: 0,
+        }
+    }
+
+    #[test]
+    fn test_payout_after_fees() {
+        // 24 SOL on the other squares, 1 SOL already on ours
+        let deployed = [SOL; 25];
+        let ev = EvParams::default().single(&deployed, 0, 0, SOL, 0);
+
+        // Win: 0.99 stake + 24 * 0.99 * 0.9 * 1/2 = 11.682 SOL, less 10% claim fee
+        let win = (99 * SOL / 100 + 24 * SOL * 99 / 100 * 9 / 10 / 2) * 9 / 10;
+        assert_eq!(ev.expected_sol, win / 25);
+        assert_eq!(ev.cost, SOL);
+        assert_eq!(ev.expected_ore, ONE_ORE / 2 * 9 / 10 / 25);
+        assert_eq!(ev.ev, (win / 25) as i64 - SOL as i64);
+        assert_eq!(ev.win_probability, 0.04);
+    }
+
+    #[test]
+    fn test_ore_and_motherlode_valuation() {
+        let params = EvParams { ore_price: SOL / 2, ..EvParams::default() };
+        let deployed = [SOL; 25];
+        let plain = params.single(&deployed, 0, 3, SOL, 0);
+        let lode = params.single(&deployed, 625 * ONE_ORE, 3, SOL, 0);
+        // A 625 ORE motherlode adds one ORE per round in expectation
+        assert_eq!(lode.expected_ore, plain.expected_ore * 2);
+        assert!(lode.ev > plain.ev);
+        assert_eq!(params.ore_value(ONE_ORE), SOL / 2);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_whole_square_gets_the_pot_without_fees(
+            deployed in prop::collection::vec(0u64..1_000 * SOL, 25),
+            square in 0usize..25,
+            stake in 1u64..100 * SOL,
+        ) {
+            // Owning the whole winning square claims everything deployed
+            let mut others = deployed.clone();
+            others[square] = 0;
+            let mut amounts = [0u64; 25];
+            amounts[square] = stake;
+            let payout = no_fees().payout(&others, 0, &amounts, square);
+            let total: u64 = others.iter().sum::<u64>() + stake;
+            prop_assert!(payout.sol <= total && total - payout.sol <= 1);
+        }
+
+        #[test]
+        fn prop_payout_monotone_in_stakes(
+            deployed in prop::collection::vec(0u64..1_000 * SOL, 25),
+            square in 0usize..25,
+            other in 0usize..25,
+            stake in 1u64..10 * SOL,
+            extra in 0u64..100 * SOL,
+        ) {
+            let params = EvParams::default();
+            let mut amounts = [0u64; 25];
+            amounts[square] = stake;
+            let base = params.payout(&deployed, 0, &amounts, square);
+
+            let mut more = deployed.clone();
+            more[other] += extra;
+            let after = params.payout(&more, 0, &amounts, square);
+            if other == square {
+                // Sharing our square never helps
+                prop_assert!(after.sol <= base.sol && after.ore <= base.ore);
+            } else {
+                // More stake on losing squares never hurts
+                prop_assert!(after.sol >= base.sol);
+            }
+        }
+
+        #[test]
+        fn prop_ev_is_claims_minus_cost(
+            deployed in prop::collection::vec(0u64..1_000 * SOL, 25),
+            amounts in prop::collection::vec(0u64..10 * SOL, 25),
+            motherlode in 0u64..1_000 * ONE_ORE,
+            tip in 0u64..SOL,
+            price in 0u64..10 * SOL,
+        ) {
+            let params = EvParams { ore_price: price, ..EvParams::default() };
+            let ev = params.evaluate(&deployed, motherlode, &amounts, tip);
+            let squares = amounts.iter().filter(|&&a| a > 0).count();
+            prop_assert_eq!(ev.cost, amounts.iter().sum::<u64>() + tip);
+            prop_assert_eq!(ev.ev, ev.expected_sol as i64 + params.ore_value(ev.expected_ore) as i64 - ev.cost as i64);
+            prop_assert_eq!(ev.win_probability, squares as f64 / 25.0);
+            // Fees only ever take: claims never exceed the pot over 25 squares
+            let pot: u64 = deployed.iter().sum::<u64>() + amounts.iter().sum::<u64>();
+            prop_assert!(ev.expected_sol as u128 <= pot as u128 * squares as u128 / 25);
+        }
+    }
+}
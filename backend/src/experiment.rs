@@ -0,0 +1,348 @@
+//! Strategy Experiments
+//!
+//! A/B comparison of strategy configs. An experiment is a named set of arms
+//! (selector, blocks, deploy amount, tip policy) assigned either per wallet
+//! or by alternating rounds. Sessions tag every deploy with the arm that
+//! produced it; per-arm results come from settled `transactions` rows.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::{ExperimentRecord, ExperimentTransaction};
+
+/// z for 95% confidence intervals
+const Z_95: f64 = 1.96;
+
+/// How an arm picks blocks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selector {
+    /// Session's AI strategy (LLM provider, else local model)
+    #[default]
+    Ai,
+    /// Local model only, even if a provider is configured
+    Local,
+    /// Deterministic N lowest-stake blocks
+    LowestStake,
+}
+
+/// How an arm sets its Jito tip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TipPolicy {
+    /// Jito's recommended tip, capped at the session's max tip
+    #[default]
+    Recommended,
+    /// Fixed tip in SOL, capped at the session's max tip
+    Fixed { sol: f64 },
+    /// Recommended tip times `factor`, capped at the session's max tip
+    Scaled { factor: f64 },
+}
+
+impl TipPolicy {
+    /// Tip in lamports given Jito's recommendation
+    pub fn apply(&self, recommended: u64, max_tip: u64) -> u64 {
+        let tip = match *self {
+            Self::Recommended => recommended,
+            Self::Fixed { sol } => (sol.max(0.0) * 1_000_000_000.0).round() as u64,
+            Self::Scaled { factor } => (recommended as f64 * factor.max(0.0)) as u64,
+        };
+        tip.min(max_tip)
+    }
+}
+
+/// One strategy config under test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentArm {
+    pub name: String,
+    #[serde(default)]
+    pub selector: Selector,
+    pub num_blocks: u8,
+    /// SOL per block
+    pub deploy_amount: f64,
+    #[serde(default)]
+    pub tip_policy: TipPolicy,
+}
+
+/// How sessions are split between arms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Assignment {
+    /// Fixed arm per wallet (wallet -> arm name)
+    Wallets { wallets: HashMap<String, String> },
+    /// Arm `round_id % arms.len()`, so every wallet plays every arm
+    AlternatingRounds,
+}
+
+/// A named set of arms and how they're assigned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Experiment {
+    pub id: Uuid,
+    pub name: String,
+    pub assignment: Assignment,
+    pub arms: Vec<ExperimentArm>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Experiment {
+    /// Decode a stored experiment
+    pub fn from_record(record: ExperimentRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id,
+            name: record.name,
+            assignment: serde_json::from_value(record.assignment)?,
+            arms: serde_json::from_value(record.arms)?,
+            status: record.status,
+            created_at: record.created_at,
+        })
+    }
+
+    /// Check arms and assignment before saving
+    pub fn validate(&self) -> Result<()> {
+        if self.arms.len() < 2 {
+            anyhow::bail!("An experiment needs at least 2 arms");
+        }
+        let mut names = HashSet::new();
+        for arm in &self.arms {
+            if arm.name.is_empty() || !names.insert(arm.name.as_str()) {
+                anyhow::bail!("Arm names must be non-empty and unique");
+            }
+            if !(1..=25).contains(&arm.num_blocks) {
+                anyhow::bail!("Arm {}: num_blocks must be 1-25", arm.name);
+            }
+            if !arm.deploy_amount.is_finite() || arm.deploy_amount < 0.0001 || arm.deploy_amount > 10.0 {
+                anyhow::bail!("Arm {}: deploy_amount must be >= 0.0001 and <= 10 (SOL)", arm.name);
+            }
+        }
+        if let Assignment::Wallets { wallets } = &self.assignment {
+            if wallets.is_empty() {
+                anyhow::bail!("Wallet assignment needs at least one wallet");
+            }
+            if let Some((wallet, arm)) = wallets.iter().find(|(_, arm)| !names.contains(arm.as_str())) {
+                anyhow::bail!("Wallet {} is assigned to unknown arm {}", wallet, arm);
+            }
+        }
+        Ok(())
+    }
+
+    /// Arm a wallet plays in a round (None if the wallet isn't enrolled)
+    pub fn arm_for(&self, wallet: &str, round_id: u64) -> Option<&ExperimentArm> {
+        match &self.assignment {
+            Assignment::Wallets { wallets } => {
+                let name = wallets.get(wallet)?;
+                self.arms.iter().find(|arm| &arm.name == name)
+            }
+            Assignment::AlternatingRounds => {
+                self.arms.get((round_id % self.arms.len().max(1) as u64) as usize)
+            }
+        }
+    }
+
+    /// Whether a wallet takes part in this experiment
+    pub fn enrolls(&self, wallet: &str) -> bool {
+        match &self.assignment {
+            Assignment::Wallets { wallets } => wallets.contains_key(wallet),
+            Assignment::AlternatingRounds => true,
+        }
+    }
+}
+
+/// Results for one arm
+///
+/// Samples are rounds: a round's deploys (one per transaction, possibly
+/// several wallets) are summed, so a split deploy isn't counted twice.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArmReport {
+    pub arm: String,
+    /// Rounds with a deploy tagged with this arm
+    pub deploys: u64,
+    /// Rounds with a known outcome
+    pub settled: u64,
+    pub wins: u64,
+    /// Rounds whose every deploy failed
+    pub failed: u64,
+    /// Lamports spent (deploys and tips) in settled rounds
+    pub total_cost: i64,
+    /// Lamports returned in settled rounds
+    pub total_reward: i64,
+    pub pnl: i64,
+    pub win_rate: Option<f64>,
+    /// Wilson 95% interval for the win rate
+    pub win_rate_ci: Option<(f64, f64)>,
+    /// Win rate of the same number of squares at random
+    pub expected_win_rate: Option<f64>,
+    /// Mean P&L per settled round (lamports)
+    pub mean_pnl: Option<f64>,
+    /// Normal-approximation 95% interval for the mean P&L
+    pub mean_pnl_ci: Option<(f64, f64)>,
+}
+
+/// Wilson score interval for `successes` out of `n`
+pub fn wilson_interval(successes: u64, n: u64, z: f64) -> Option<(f64, f64)> {
+    if n == 0 {
+        return None;
+    }
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    Some(((center - half).max(0.0), (center + half).min(1.0)))
+}
+
+/// Mean and normal-approximation interval (needs at least 2 samples)
+pub fn mean_interval(values: &[f64], z: f64) -> Option<(f64, (f64, f64))> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let half = z * (variance / n).sqrt();
+    Some((mean, (mean - half, mean + half)))
+}
+
+/// One arm's deploys in one round
+#[derive(Default)]
+struct RoundSample {
+    settled: bool,
+    won: bool,
+    failed: bool,
+    cost: i64,
+    reward: i64,
+    squares: HashSet<i16>,
+}
+
+/// Per-arm results from an experiment's tagged transactions, grouped by (arm, round)
+pub fn report(experiment: &Experiment, rows: &[ExperimentTransaction]) -> Vec<ArmReport> {
+    experiment.arms.iter().map(|arm| {
+        let mut rounds: HashMap<i64, RoundSample> = HashMap::new();
+        for row in rows.iter().filter(|row| row.experiment_arm == arm.name) {
+            let round = rounds.entry(row.round_id).or_insert_with(|| RoundSample { failed: true, ..Default::default() });
+            round.failed &= row.status == "failed";
+            if row.status == "won" || row.status == "lost" {
+                round.settled = true;
+                round.won |= row.status == "won";
+                round.cost += row.cost();
+                round.reward += row.actual_reward.unwrap_or(0);
+                round.squares.extend(&row.squares);
+            }
+        }
+        let settled: Vec<&RoundSample> = rounds.values().filter(|round| round.settled).collect();
+
+        let pnls: Vec<f64> = settled.iter().map(|round| (round.reward - round.cost) as f64).collect();
+        let wins = settled.iter().filter(|round| round.won).count() as u64;
+        let total_cost: i64 = settled.iter().map(|round| round.cost).sum();
+        let total_reward: i64 = settled.iter().map(|round| round.reward).sum();
+        let squares: usize = settled.iter().map(|round| round.squares.len()).sum();
+        let n = settled.len() as u64;
+        let mean = mean_interval(&pnls, Z_95);
+
+        ArmReport {
+            arm: arm.name.clone(),
+            deploys: rounds.len() as u64,
+            settled: n,
+            wins,
+            failed: rounds.values().filter(|round| round.failed).count() as u64,
+            total_cost,
+            total_reward,
+            pnl: total_reward - total_cost,
+            win_rate: (n > 0).then(|| wins as f64 / n as f64),
+            win_rate_ci: wilson_interval(wins, n, Z_95),
+            expected_win_rate: (n > 0).then(|| squares as f64 / n as f64 / 25.0),
+            mean_pnl: mean.map(|(m, _)| m).or_else(|| pnls.first().copied()),
+            mean_pnl_ci: mean.map(|(_, ci)| ci),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(assignment: Assignment) -> Experiment {
+        let arm = |name: &str, num_blocks| ExperimentArm {
+            name: name.to_string(),
+            selector: Selector::LowestStake,
+            num_blocks,
+            deploy_amount: 0.01,
+            tip_policy: TipPolicy::Recommended,
+        };
+        Experiment {
+            id: Uuid::new_v4(),
+            name: "blocks".to_string(),
+            assignment,
+            arms: vec![arm("one", 1), arm("five", 5)],
+            status: "active".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_assignment() {
+        let alternating = experiment(Assignment::AlternatingRounds);
+        assert_eq!(alternating.arm_for("any", 10).unwrap().name, "one");
+        assert_eq!(alternating.arm_for("any", 11).unwrap().name, "five");
+
+        let by_wallet = experiment(Assignment::Wallets {
+            wallets: HashMap::from([("w1".to_string(), "five".to_string())]),
+        });
+        assert!(by_wallet.validate().is_ok());
+        assert_eq!(by_wallet.arm_for("w1", 10).unwrap().name, "five");
+        assert!(by_wallet.arm_for("w2", 10).is_none());
+
+        let bad = experiment(Assignment::Wallets {
+            wallets: HashMap::from([("w1".to_string(), "ten".to_string())]),
+        });
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_intervals() {
+        let (lo, hi) = wilson_interval(5, 10, Z_95).unwrap();
+        assert!((lo - 0.2366).abs() < 1e-3 && (hi - 0.7634).abs() < 1e-3);
+        assert_eq!(wilson_interval(0, 0, Z_95), None);
+
+        let (mean, (lo, hi)) = mean_interval(&[1.0, 2.0, 3.0], Z_95).unwrap();
+        assert_eq!(mean, 2.0);
+        assert!((hi - mean - 1.96 / 3f64.sqrt()).abs() < 1e-9 && lo < mean);
+    }
+
+    #[test]
+    fn test_report_counts_rounds() {
+        let row = |arm: &str, round_id, status: &str, reward| ExperimentTransaction {
+            experiment_arm: arm.to_string(),
+            round_id,
+            status: status.to_string(),
+            deploy_amount: 100,
+            tip_amount: 10,
+            actual_reward: reward,
+            squares: vec![round_id as i16],
+        };
+        // Round 1's deploy went out in two transactions; round 2's failed
+        let rows = vec![
+            row("one", 1, "won", Some(500)),
+            row("one", 1, "lost", Some(0)),
+            row("one", 2, "failed", None),
+            row("five", 1, "lost", Some(0)),
+        ];
+        let reports = report(&experiment(Assignment::AlternatingRounds), &rows);
+        let one = &reports[0];
+        assert_eq!((one.deploys, one.settled, one.wins, one.failed), (2, 1, 1, 1));
+        assert_eq!((one.total_cost, one.total_reward, one.pnl), (220, 500, 280));
+        assert_eq!(one.expected_win_rate, Some(1.0 / 25.0));
+        assert_eq!((reports[1].settled, reports[1].wins), (1, 0));
+    }
+
+    #[test]
+    fn test_tip_policy() {
+        assert_eq!(TipPolicy::Recommended.apply(1_000, 500), 500);
+        assert_eq!(TipPolicy::Scaled { factor: 2.0 }.apply(1_000, 5_000), 2_000);
+        assert_eq!(TipPolicy::Fixed { sol: 0.000001 }.apply(0, 5_000), 1_000);
+    }
+}
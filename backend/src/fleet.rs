@@ -0,0 +1,133 @@
+//! Fleet Sessions
+//!
+//! One session mining with several managed wallets. The fleet makes one
+//! decision per round, with the lead (first) wallet's config, and splits the
+//! deploy across its wallets by a policy:
+//!
+//! - `round_robin`: the whole deploy goes to one wallet, rotating each round
+//! - `balance_weighted`: every wallet deploys on the same squares, with a
+//!   share of each amount in proportion to its balance
+//! - `square_per_wallet`: the squares are dealt out, one per wallet
+//!
+//! Each wallet has its own `sessions` row, so P&L is kept per wallet and
+//! summed for the fleet.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::allocation::Allocation;
+
+/// How a fleet splits each round's deploy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FleetSplit {
+    #[default]
+    RoundRobin,
+    BalanceWeighted,
+    SquarePerWallet,
+}
+
+impl FleetSplit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FleetSplit::RoundRobin => "round_robin",
+            FleetSplit::BalanceWeighted => "balance_weighted",
+            FleetSplit::SquarePerWallet => "square_per_wallet",
+        }
+    }
+
+    /// Split `allocation` across wallets with `balances` (lamports; 0 = sits
+    /// the round out). Returns one allocation per wallet, empty for wallets
+    /// that don't deploy.
+    pub fn split(&self, allocation: &Allocation, balances: &[u64], round_id: u64) -> Vec<Allocation> {
+        let n = balances.len();
+        let mut legs = vec![Allocation::default(); n];
+        let able: Vec<usize> = (0..n).filter(|&i| balances[i] > 0).collect();
+        if able.is_empty() {
+            return legs;
+        }
+
+        match self {
+            FleetSplit::RoundRobin => {
+                // The wallet whose turn it is, or the next one that can cover the deploy
+                let start = (round_id % n as u64) as usize;
+                if let Some(i) = (0..n).map(|k| (start + k) % n).find(|&i| balances[i] >= allocation.total()) {
+                    legs[i] = *allocation;
+                }
+            }
+            FleetSplit::BalanceWeighted => {
+                let sum: u64 = able.iter().map(|&i| balances[i]).sum();
+                for &i in &able {
+                    legs[i] = allocation.scaled(balances[i] as f64 / sum as f64);
+                }
+            }
+            FleetSplit::SquarePerWallet => {
+                let offset = (round_id % able.len() as u64) as usize;
+                let mut dealt: Vec<(Vec<u8>, Vec<u64>)> = vec![(Vec::new(), Vec::new()); n];
+                for (k, square) in allocation.squares().into_iter().enumerate() {
+                    let (squares, amounts) = &mut dealt[able[(k + offset) % able.len()]];
+                    squares.push(square);
+                    amounts.push(allocation.amounts()[square as usize]);
+                }
+                for (leg, (squares, amounts)) in legs.iter_mut().zip(dealt) {
+                    *leg = Allocation::from_squares(&squares, &amounts);
+                }
+            }
+        }
+        legs
+    }
+}
+
+/// A wallet in a fleet and its session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FleetMember {
+    pub wallet: String,
+    pub session_id: Uuid,
+}
+
+/// A running fleet; the first member is the lead
+#[derive(Debug, Clone, Serialize)]
+pub struct Fleet {
+    pub id: Uuid,
+    pub split: FleetSplit,
+    pub members: Vec<FleetMember>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn deploy() -> Allocation {
+        Allocation::from_squares(&[2, 4, 6], &[SOL / 100, SOL / 50, SOL / 100])
+    }
+
+    #[test]
+    fn test_round_robin_rotates_past_short_wallets() {
+        let balances = [SOL, SOL, SOL];
+        let legs = FleetSplit::RoundRobin.split(&deploy(), &balances, 7);
+        assert_eq!(legs[1], deploy());
+        assert_eq!(legs[0].total() + legs[2].total(), 0);
+
+        // Wallet 1 can't cover it, so wallet 2 goes
+        let legs = FleetSplit::RoundRobin.split(&deploy(), &[SOL, SOL / 100, SOL], 7);
+        assert_eq!(legs[2], deploy());
+        assert!(FleetSplit::RoundRobin.split(&deploy(), &[0, 0], 1).iter().all(|l| l.total() == 0));
+    }
+
+    #[test]
+    fn test_weighted_and_dealt_splits_cover_the_deploy() {
+        let legs = FleetSplit::BalanceWeighted.split(&deploy(), &[3 * SOL, SOL, 0], 1);
+        assert_eq!(legs[0].squares(), deploy().squares());
+        assert_eq!(legs[0].amounts()[4], 3 * legs[1].amounts()[4]);
+        assert_eq!(legs[2].total(), 0);
+        assert!(deploy().total() - legs.iter().map(Allocation::total).sum::<u64>() < 10);
+
+        let legs = FleetSplit::SquarePerWallet.split(&deploy(), &[SOL, 0, SOL], 0);
+        assert_eq!(legs[0].squares(), vec![2, 6]);
+        assert_eq!(legs[2].squares(), vec![4]);
+        assert_eq!(legs[2].amounts()[4], SOL / 50);
+        assert_eq!(legs.iter().map(Allocation::total).sum::<u64>(), deploy().total());
+    }
+}
@@ -1,23 +1,28 @@
 //! Jito Bundle Submission Client
 //! 
-//! Handles bundle submission to Jito block engine for MEV-protected transactions.
+//! Handles bundle submission to Jito block engines for MEV-protected transactions.
 //! Uses Jito's JSON-RPC API for bundle submission.
-//! Block Engine: ny.mainnet.block-engine.jito.wtf
+//! Bundles are fanned out to every enabled regional block engine concurrently;
+//! per-region acceptance and latency are tracked so slow regions drop out.
 
-use std::time::Duration;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, Context};
 use base64::Engine;
+use parking_lot::RwLock;
+use serde::Serialize;
 use solana_sdk::{
     instruction::Instruction,
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
     system_instruction,
 };
 use tracing::{debug, info, warn, error};
 
-/// Jito tip account addresses (rotate for load balancing)
+/// Fallback Jito tip accounts, used until `getTipAccounts` succeeds
 const JITO_TIP_ACCOUNTS: [&str; 8] = [
     "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
     "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
@@ -29,8 +34,33 @@ const JITO_TIP_ACCOUNTS: [&str; 8] = [
     "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
 ];
 
-/// Jito Block Engine RPC endpoints (NY for lower latency from East US)
-const JITO_MAINNET_RPC: &str = "https://ny.mainnet.block-engine.jito.wtf/api/v1/bundles";
+/// Regional block engines used when none are configured (NY first - closest to the VPS)
+pub const DEFAULT_BLOCK_ENGINES: [&str; 5] = [
+    "ny.mainnet.block-engine.jito.wtf",
+    "amsterdam.mainnet.block-engine.jito.wtf",
+    "frankfurt.mainnet.block-engine.jito.wtf",
+    "tokyo.mainnet.block-engine.jito.wtf",
+    "slc.mainnet.block-engine.jito.wtf",
+];
+
+/// Per-request timeout for a single region. Bundles land in the last ~2s of a
+/// round, so a region slower than this is useless anyway.
+const REGION_REQUEST_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Minimum submissions before a region can be dropped
+const REGION_MIN_SAMPLES: u64 = 10;
+
+/// Drop a region whose acceptance rate falls below this
+const REGION_MIN_ACCEPTANCE: f64 = 0.5;
+
+/// Drop a region whose average latency exceeds this multiple of the fastest region
+const REGION_SLOW_FACTOR: f64 = 2.5;
+
+/// Latency floor below which a region is never considered slow (ms)
+const REGION_SLOW_FLOOR_MS: f64 = 250.0;
+
+/// Disabled regions are retried after this long
+const REGION_RETRY_AFTER: Duration = Duration::from_secs(300);
 
 /// Bundle status returned by Jito
 #[derive(Debug, Clone)]
@@ -48,35 +78,225 @@ pub struct BundleResult {
     pub status: BundleStatus,
     pub tip_amount: u64,
     pub signatures: Vec<Signature>,
+    /// Regions that accepted the bundle
+    pub accepted_regions: Vec<String>,
+}
+
+/// Result of `simulateBundle`
+#[derive(Debug, Clone)]
+pub struct BundleSimulation {
+    /// Error of the first failing transaction, if any
+    pub error: Option<TransactionError>,
+    /// Index of the failing transaction within the bundle
+    pub failed_tx: Option<usize>,
+    /// Raw error JSON, kept when it doesn't decode into a `TransactionError`
+    pub raw_error: Option<String>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+/// Acceptance and latency counters for one block engine region
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RegionStats {
+    pub submitted: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub errors: u64,
+    /// Exponentially weighted moving average latency (ms)
+    pub avg_latency_ms: f64,
+    pub last_latency_ms: u64,
+    pub enabled: bool,
+    pub disabled_reason: Option<String>,
+    #[serde(skip)]
+    disabled_at: Option<Instant>,
+}
+
+impl RegionStats {
+    fn new() -> Self {
+        Self { enabled: true, ..Default::default() }
+    }
+
+    /// Fraction of submissions the region accepted
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.submitted == 0 {
+            1.0
+        } else {
+            self.accepted as f64 / self.submitted as f64
+        }
+    }
+
+    fn record(&mut self, latency: Duration, outcome: &RegionOutcome) {
+        let latency_ms = latency.as_millis() as u64;
+        self.submitted += 1;
+        self.last_latency_ms = latency_ms;
+        self.avg_latency_ms = if self.submitted == 1 {
+            latency_ms as f64
+        } else {
+            self.avg_latency_ms * 0.8 + latency_ms as f64 * 0.2
+        };
+        match outcome {
+            RegionOutcome::Accepted { .. } => self.accepted += 1,
+            RegionOutcome::Rejected { .. } => self.rejected += 1,
+            RegionOutcome::NetworkError { .. } => self.errors += 1,
+        }
+    }
+}
+
+/// A regional block engine endpoint
+#[derive(Debug)]
+struct Region {
+    name: String,
+    bundles_url: String,
+    stats: RwLock<RegionStats>,
+}
+
+/// Outcome of submitting a bundle to one region
+#[derive(Debug, Clone)]
+enum RegionOutcome {
+    Accepted { bundle_id: Option<String> },
+    Rejected { reason: String },
+    NetworkError { reason: String },
 }
 
 /// Jito client for bundle submission
 #[derive(Clone)]
 pub struct JitoClient {
-    block_engine_url: String,
+    regions: Arc<Vec<Region>>,
+    tip_accounts: Arc<RwLock<Vec<Pubkey>>>,
+    http: reqwest::Client,
+}
+
+/// Short region name from a block engine host ("ny.mainnet.block-engine.jito.wtf" -> "ny")
+fn region_name(host: &str) -> String {
+    host.split('.').next().unwrap_or(host).to_string()
+}
+
+/// Full bundles endpoint for a block engine host or URL
+fn bundles_url(block_engine: &str) -> String {
+    let host = block_engine
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    let host = host.split('/').next().unwrap_or(host);
+    format!("https://{}/api/v1/bundles", host)
 }
 
 impl JitoClient {
-    /// Create a new Jito client
-    pub async fn new(block_engine_url: &str) -> Result<Self> {
-        let url = if block_engine_url.contains("block-engine") {
-            format!("https://{}/api/v1/bundles", block_engine_url.trim_start_matches("https://").trim_start_matches("http://"))
-        } else {
-            JITO_MAINNET_RPC.to_string()
+    /// Create a new Jito client for a set of regional block engines
+    ///
+    /// Tip accounts are refreshed from `getTipAccounts`; the built-in list is
+    /// kept if every region fails to answer.
+    pub async fn new(block_engines: &[String]) -> Result<Self> {
+        let mut engines: Vec<&str> = block_engines.iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if engines.is_empty() {
+            engines = DEFAULT_BLOCK_ENGINES.to_vec();
+        }
+        
+        let mut seen = HashSet::new();
+        let regions: Vec<Region> = engines.into_iter()
+            .map(bundles_url)
+            .filter(|url| seen.insert(url.clone()))
+            .map(|url| {
+                let host = url.trim_start_matches("https://");
+                Region {
+                    name: region_name(host),
+                    bundles_url: url.clone(),
+                    stats: RwLock::new(RegionStats::new()),
+                }
+            })
+            .collect();
+        
+        for region in &regions {
+            info!("Initializing Jito region {}: {}", region.name, region.bundles_url);
+        }
+        
+        let http = reqwest::Client::builder()
+            .timeout(REGION_REQUEST_TIMEOUT)
+            .build()
+            .context("Failed to create HTTP client")?;
+        
+        let default_tip_accounts = JITO_TIP_ACCOUNTS.iter()
+            .map(|s| s.parse().expect("Invalid tip account"))
+            .collect();
+        
+        let client = Self {
+            regions: Arc::new(regions),
+            tip_accounts: Arc::new(RwLock::new(default_tip_accounts)),
+            http,
         };
         
-        info!("Initializing Jito client for: {}", url);
+        match client.refresh_tip_accounts().await {
+            Ok(count) => info!("Loaded {} Jito tip accounts from block engine", count),
+            Err(e) => warn!("Failed to refresh Jito tip accounts, using built-in list: {}", e),
+        }
         
-        Ok(Self {
-            block_engine_url: url,
-        })
+        Ok(client)
+    }
+    
+    /// Refresh tip accounts via `getTipAccounts`, trying each region in turn
+    pub async fn refresh_tip_accounts(&self) -> Result<usize> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getTipAccounts",
+            "params": []
+        });
+        
+        let mut last_error = None;
+        for region in self.regions.iter() {
+            let response = match self.http.post(&region.bundles_url).json(&request).send().await {
+                Ok(r) => r,
+                Err(e) => {
+                    debug!("getTipAccounts failed for {}: {}", region.name, e);
+                    last_error = Some(e.to_string());
+                    continue;
+                }
+            };
+            
+            let body: serde_json::Value = match response.json().await {
+                Ok(b) => b,
+                Err(e) => {
+                    last_error = Some(e.to_string());
+                    continue;
+                }
+            };
+            
+            let accounts: Vec<Pubkey> = body["result"]
+                .as_array()
+                .map(|arr| arr.iter()
+                    .filter_map(|v| v.as_str())
+                    .filter_map(|s| s.parse().ok())
+                    .collect())
+                .unwrap_or_default();
+            
+            if accounts.is_empty() {
+                last_error = Some(format!("{} returned no tip accounts: {}", region.name, body));
+                continue;
+            }
+            
+            let count = accounts.len();
+            *self.tip_accounts.write() = accounts;
+            return Ok(count);
+        }
+        
+        anyhow::bail!(last_error.unwrap_or_else(|| "No block engine regions configured".to_string()))
+    }
+    
+    /// Per-region acceptance and latency stats
+    pub fn region_stats(&self) -> Vec<(String, RegionStats)> {
+        self.regions.iter()
+            .map(|r| (r.name.clone(), r.stats.read().clone()))
+            .collect()
     }
     
     /// Get a random tip account for load balancing
     pub fn get_tip_account(&self) -> Pubkey {
-        use rand::Rng;
-        let idx = rand::thread_rng().gen_range(0..JITO_TIP_ACCOUNTS.len());
-        JITO_TIP_ACCOUNTS[idx].parse().expect("Invalid tip account")
+        use rand::seq::SliceRandom;
+        let accounts = self.tip_accounts.read();
+        *accounts.choose(&mut rand::thread_rng()).expect("No tip accounts")
     }
     
     /// Build a tip instruction
@@ -102,12 +322,16 @@ impl JitoClient {
         all_instructions.push(self.build_tip_instruction(payer, tip_amount));
         
         // Build transaction (will need to be signed by wallet)
-        let tx = Transaction::new_with_payer(&all_instructions, Some(payer));
+        let mut tx = Transaction::new_with_payer(&all_instructions, Some(payer));
+        tx.message.recent_blockhash = recent_blockhash;
         
         Ok(tx)
     }
     
-    /// Submit a bundle to Jito via JSON-RPC
+    /// Submit a bundle to every enabled region concurrently via JSON-RPC
+    ///
+    /// Regions return the same bundle ID for the same transactions, so
+    /// results are de-duplicated into a single `BundleResult`.
     pub async fn send_bundle(
         &self,
         transactions: Vec<Transaction>,
@@ -120,6 +344,116 @@ impl JitoClient {
             }
         }
         
+        // Collect signatures
+        let signatures: Vec<Signature> = transactions.iter()
+            .flat_map(|tx| tx.signatures.clone())
+            .collect();
+        
+        let regions = self.active_regions();
+        info!(
+            "Submitting bundle with {} transaction(s) to {} Jito region(s)",
+            transactions.len(),
+            regions.len()
+        );
+        
+        let txs = &transactions;
+        let submissions = regions.iter().map(|region| async move {
+            let started = Instant::now();
+            let outcome = self.submit_to_region(region, txs).await;
+            let latency = started.elapsed();
+            region.stats.write().record(latency, &outcome);
+            debug!("Jito region {} answered in {}ms: {:?}", region.name, latency.as_millis(), outcome);
+            (region.name.clone(), outcome)
+        });
+        let outcomes = futures::future::join_all(submissions).await;
+        self.update_region_health();
+        
+        let mut bundle_ids: Vec<String> = Vec::new();
+        let mut accepted_regions = Vec::new();
+        let mut failures = Vec::new();
+        for (name, outcome) in outcomes {
+            match outcome {
+                RegionOutcome::Accepted { bundle_id } => {
+                    if let Some(id) = bundle_id {
+                        if !bundle_ids.contains(&id) {
+                            bundle_ids.push(id);
+                        }
+                    }
+                    accepted_regions.push(name);
+                }
+                RegionOutcome::Rejected { reason } | RegionOutcome::NetworkError { reason } => {
+                    failures.push(format!("{}: {}", name, reason));
+                }
+            }
+        }
+        
+        if bundle_ids.len() > 1 {
+            warn!("Jito regions returned different bundle IDs: {:?}", bundle_ids);
+        }
+        let bundle_id = bundle_ids.into_iter().next()
+            .unwrap_or_else(|| format!("bundle_{}", uuid::Uuid::new_v4()));
+        
+        if accepted_regions.is_empty() {
+            error!("Bundle {} rejected by all regions: {}", bundle_id, failures.join("; "));
+            return Ok(BundleResult {
+                bundle_id,
+                status: BundleStatus::Failed {
+                    reason: if failures.is_empty() {
+                        "No Jito regions enabled".to_string()
+                    } else {
+                        failures.join("; ")
+                    },
+                },
+                tip_amount: 0,
+                signatures,
+                accepted_regions,
+            });
+        }
+        
+        info!("Bundle {} accepted by {:?}", bundle_id, accepted_regions);
+        Ok(BundleResult {
+            bundle_id,
+            status: BundleStatus::Pending,
+            tip_amount: self.extract_tip_amount(&transactions),
+            signatures,
+            accepted_regions,
+        })
+    }
+    
+    /// Regions currently eligible for submission (re-admitting any whose
+    /// retry cooldown has elapsed)
+    fn active_regions(&self) -> Vec<&Region> {
+        for region in self.regions.iter() {
+            let mut stats = region.stats.write();
+            if !stats.enabled && stats.disabled_at.map_or(true, |t| t.elapsed() >= REGION_RETRY_AFTER) {
+                info!("Re-enabling Jito region {} for another trial", region.name);
+                *stats = RegionStats::new();
+            }
+        }
+        self.regions.iter().filter(|r| r.stats.read().enabled).collect()
+    }
+    
+    /// Drop regions that are slow or keep rejecting bundles. At least one
+    /// region always stays enabled.
+    fn update_region_health(&self) {
+        let snapshot: Vec<RegionStats> = self.regions.iter().map(|r| r.stats.read().clone()).collect();
+        let verdicts = evaluate_regions(&snapshot);
+        
+        for (region, verdict) in self.regions.iter().zip(verdicts) {
+            if let Some(reason) = verdict {
+                let mut stats = region.stats.write();
+                if stats.enabled {
+                    warn!("Dropping Jito region {}: {}", region.name, reason);
+                    stats.enabled = false;
+                    stats.disabled_reason = Some(reason);
+                    stats.disabled_at = Some(Instant::now());
+                }
+            }
+        }
+    }
+    
+    /// Submit to a single region, retrying with base58 if base64 can't be decoded
+    async fn submit_to_region(&self, region: &Region, transactions: &[Transaction]) -> RegionOutcome {
         #[derive(Clone, Copy, Debug, PartialEq, Eq)]
         enum TxEncoding {
             Base64,
@@ -138,26 +472,6 @@ impl JitoClient {
                 })
                 .collect()
         };
-        
-        // Collect signatures
-        let signatures: Vec<Signature> = transactions.iter()
-            .flat_map(|tx| tx.signatures.clone())
-            .collect();
-        
-        // Generate bundle ID
-        let bundle_id = format!("bundle_{}", uuid::Uuid::new_v4());
-        
-        info!(
-            "Submitting bundle {} with {} transaction(s) to Jito",
-            bundle_id,
-            transactions.len()
-        );
-        
-        // Create HTTP client
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .context("Failed to create HTTP client")?;
 
         let mut last_http_error: Option<String> = None;
         for encoding in [TxEncoding::Base64, TxEncoding::Base58] {
@@ -169,22 +483,14 @@ impl JitoClient {
                 "params": [serialized_txs]
             });
 
-            let response = match client
-                .post(&self.block_engine_url)
+            let response = match self.http
+                .post(&region.bundles_url)
                 .json(&request)
                 .send()
                 .await
             {
                 Ok(r) => r,
-                Err(e) => {
-                    error!("Bundle {} network error: {}", bundle_id, e);
-                    return Ok(BundleResult {
-                        bundle_id,
-                        status: BundleStatus::Failed { reason: e.to_string() },
-                        tip_amount: 0,
-                        signatures,
-                    });
-                }
+                Err(e) => return RegionOutcome::NetworkError { reason: e.to_string() },
             };
 
             if response.status().is_success() {
@@ -194,36 +500,17 @@ impl JitoClient {
                     .unwrap_or_else(|_| serde_json::json!({}));
 
                 if let Some(err_val) = result.get("error") {
-                    error!("Bundle {} failed: {:?}", bundle_id, err_val);
-                    return Ok(BundleResult {
-                        bundle_id,
-                        status: BundleStatus::Failed {
-                            reason: err_val.to_string(),
-                        },
-                        tip_amount: 0,
-                        signatures,
-                    });
+                    return RegionOutcome::Rejected { reason: err_val.to_string() };
                 }
 
-                info!(
-                    "Bundle {} submitted successfully (encoding: {:?})",
-                    bundle_id, encoding
-                );
-                return Ok(BundleResult {
-                    bundle_id,
-                    status: BundleStatus::Pending,
-                    tip_amount: self.extract_tip_amount(&transactions),
-                    signatures,
-                });
+                return RegionOutcome::Accepted {
+                    bundle_id: result["result"].as_str().map(|s| s.to_string()),
+                };
             }
 
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             let reason = format!("HTTP {}: {}", status, body);
-            error!(
-                "Bundle {} HTTP error (encoding: {:?}) {}",
-                bundle_id, encoding, reason
-            );
 
             let body_lc = reason.to_lowercase();
             let is_decode_error = body_lc.contains("could not be decoded")
@@ -231,27 +518,17 @@ impl JitoClient {
                 || body_lc.contains("cannot be decoded");
 
             if is_decode_error && encoding == TxEncoding::Base64 {
-                warn!("Jito decode error; retrying bundle with base58 encoding");
+                warn!("Jito {} decode error; retrying bundle with base58 encoding", region.name);
                 last_http_error = Some(reason);
                 continue;
             }
 
-            return Ok(BundleResult {
-                bundle_id,
-                status: BundleStatus::Failed { reason },
-                tip_amount: 0,
-                signatures,
-            });
+            return RegionOutcome::Rejected { reason };
         }
 
-        Ok(BundleResult {
-            bundle_id,
-            status: BundleStatus::Failed {
-                reason: last_http_error.unwrap_or_else(|| "Unknown Jito submission failure".to_string()),
-            },
-            tip_amount: 0,
-            signatures,
-        })
+        RegionOutcome::Rejected {
+            reason: last_http_error.unwrap_or_else(|| "Unknown Jito submission failure".to_string()),
+        }
     }
     
     /// Submit a single transaction as a bundle
@@ -259,11 +536,80 @@ impl JitoClient {
         self.send_bundle(vec![tx]).await
     }
     
+    /// Simulate a bundle with `simulateBundle` on the first enabled region
+    pub async fn simulate_bundle(&self, transactions: &[Transaction]) -> Result<BundleSimulation> {
+        let region = self.active_regions().into_iter().next()
+            .context("No Jito regions enabled")?;
+        
+        let encoded: Vec<String> = transactions.iter()
+            .map(|tx| bincode::serialize(tx).map(|b| base64::engine::general_purpose::STANDARD.encode(b)))
+            .collect::<std::result::Result<_, _>>()
+            .context("Failed to serialize tx")?;
+        
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "simulateBundle",
+            "params": [
+                { "encodedTransactions": encoded },
+                {
+                    "skipSigVerify": true,
+                    "replaceRecentBlockhash": false,
+                    "preExecutionAccountsConfigs": vec![serde_json::Value::Null; transactions.len()],
+                    "postExecutionAccountsConfigs": vec![serde_json::Value::Null; transactions.len()]
+                }
+            ]
+        });
+        
+        let body: serde_json::Value = self.http
+            .post(&region.bundles_url)
+            .json(&request)
+            .send()
+            .await
+            .context("simulateBundle request failed")?
+            .json()
+            .await
+            .context("Failed to parse simulateBundle response")?;
+        
+        if let Some(err) = body.get("error") {
+            anyhow::bail!("simulateBundle error from {}: {}", region.name, err);
+        }
+        
+        let value = &body["result"]["value"];
+        let results = value["transactionResults"].as_array().cloned().unwrap_or_default();
+        
+        let failed_tx = results.iter().position(|r| !r["err"].is_null());
+        let error = failed_tx
+            .and_then(|i| serde_json::from_value::<TransactionError>(results[i]["err"].clone()).ok());
+        let raw_error = failed_tx.map(|i| results[i]["err"].to_string());
+        
+        // A bundle can fail before any transaction result is produced
+        if failed_tx.is_none() && value["summary"] != "succeeded" && !value["summary"].is_null() {
+            anyhow::bail!("simulateBundle failed: {}", value["summary"]);
+        }
+        
+        let logs = results.iter()
+            .flat_map(|r| r["logs"].as_array().cloned().unwrap_or_default())
+            .filter_map(|l| l.as_str().map(|s| s.to_string()))
+            .collect();
+        let units_consumed = results.iter()
+            .filter_map(|r| r["unitsConsumed"].as_u64())
+            .reduce(|a, b| a + b);
+        
+        Ok(BundleSimulation {
+            error,
+            failed_tx,
+            raw_error,
+            logs,
+            units_consumed,
+        })
+    }
+    
     /// Extract total tip amount from transactions
     fn extract_tip_amount(&self, transactions: &[Transaction]) -> u64 {
-        let tip_accounts: std::collections::HashSet<Pubkey> = JITO_TIP_ACCOUNTS
+        let tip_accounts: HashSet<Pubkey> = self.tip_accounts.read()
             .iter()
-            .map(|s| s.parse().unwrap())
+            .copied()
             .collect();
         
         let mut total_tip = 0u64;
@@ -340,6 +686,47 @@ impl JitoClient {
     }
 }
 
+/// Decide which regions to drop. Returns a reason for each region that
+/// should be disabled, judged against the fastest healthy region.
+fn evaluate_regions(stats: &[RegionStats]) -> Vec<Option<String>> {
+    let best_latency = stats.iter()
+        .filter(|s| s.enabled && s.accepted > 0)
+        .map(|s| s.avg_latency_ms)
+        .fold(f64::INFINITY, f64::min);
+    let slow_threshold = (best_latency * REGION_SLOW_FACTOR).max(REGION_SLOW_FLOOR_MS);
+    
+    let mut verdicts: Vec<Option<String>> = stats.iter().map(|s| {
+        if !s.enabled || s.submitted < REGION_MIN_SAMPLES {
+            return None;
+        }
+        if s.acceptance_rate() < REGION_MIN_ACCEPTANCE {
+            return Some(format!("acceptance {:.0}% below {:.0}%",
+                s.acceptance_rate() * 100.0, REGION_MIN_ACCEPTANCE * 100.0));
+        }
+        if best_latency.is_finite() && s.avg_latency_ms > slow_threshold {
+            return Some(format!("avg latency {:.0}ms above {:.0}ms",
+                s.avg_latency_ms, slow_threshold));
+        }
+        None
+    }).collect();
+    
+    // Never drop the last enabled region
+    let remaining = stats.iter().zip(&verdicts)
+        .filter(|(s, v)| s.enabled && v.is_none())
+        .count();
+    if remaining == 0 {
+        if let Some(best) = stats.iter().enumerate()
+            .filter(|(_, s)| s.enabled)
+            .min_by(|a, b| a.1.avg_latency_ms.partial_cmp(&b.1.avg_latency_ms).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+        {
+            verdicts[best] = None;
+        }
+    }
+    
+    verdicts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,4 +737,33 @@ mod tests {
             assert!(account.parse::<Pubkey>().is_ok(), "Invalid tip account: {}", account);
         }
     }
+    
+    #[test]
+    fn test_region_urls() {
+        assert_eq!(bundles_url("ny.mainnet.block-engine.jito.wtf"), "https://ny.mainnet.block-engine.jito.wtf/api/v1/bundles");
+        assert_eq!(bundles_url("https://tokyo.mainnet.block-engine.jito.wtf/api/v1/bundles"), "https://tokyo.mainnet.block-engine.jito.wtf/api/v1/bundles");
+        assert_eq!(region_name("amsterdam.mainnet.block-engine.jito.wtf"), "amsterdam");
+    }
+    
+    #[test]
+    fn test_slow_region_dropped() {
+        let region = |accepted: u64, latency: f64| RegionStats {
+            submitted: 20,
+            accepted,
+            avg_latency_ms: latency,
+            ..RegionStats::new()
+        };
+        let stats = vec![region(20, 40.0), region(20, 900.0), region(5, 50.0)];
+        let verdicts = evaluate_regions(&stats);
+        
+        assert!(verdicts[0].is_none());
+        assert!(verdicts[1].is_some(), "slow region should be dropped");
+        assert!(verdicts[2].is_some(), "rejecting region should be dropped");
+    }
+    
+    #[test]
+    fn test_last_region_kept() {
+        let stats = vec![RegionStats { submitted: 20, accepted: 0, ..RegionStats::new() }];
+        assert!(evaluate_regions(&stats)[0].is_none());
+    }
 }
@@ -0,0 +1,306 @@
+//! LLM Providers
+//!
+//! Chat-completion backends for `AiStrategy`. OpenRouter and any
+//! OpenAI-compatible endpoint (a local llama.cpp or Ollama server, for
+//! example) share one request path; providers are registered at startup and
+//! picked per session by name, with per-session model and sampling overrides.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+use tracing::debug;
+
+/// OpenRouter API base URL
+pub const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
+/// Default model for ultra-fast mining decisions
+pub const DEFAULT_MODEL: &str = "google/gemini-3-flash-preview";
+
+/// Model and sampling settings for a provider
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmConfig {
+    pub model: String,
+    pub temperature: f32,
+    #[serde(with = "duration_ms")]
+    pub timeout: Duration,
+    pub max_tokens: u32,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            model: DEFAULT_MODEL.to_string(),
+            temperature: 0.3,
+            timeout: Duration::from_millis(2_000),
+            max_tokens: 300,
+        }
+    }
+}
+
+mod duration_ms {
+    use serde::Serializer;
+    use tokio::time::Duration;
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(d.as_millis() as u64)
+    }
+}
+
+/// Per-session overrides for a registered provider
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LlmOverrides {
+    /// Registered provider name (defaults to the server default)
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub timeout_ms: Option<u64>,
+    pub max_tokens: Option<u32>,
+}
+
+impl LlmOverrides {
+    fn apply(&self, mut config: LlmConfig) -> LlmConfig {
+        if let Some(model) = &self.model {
+            config.model = model.clone();
+        }
+        if let Some(temperature) = self.temperature {
+            config.temperature = temperature.clamp(0.0, 2.0);
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            config.timeout = Duration::from_millis(timeout_ms.clamp(100, 30_000));
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config.max_tokens = max_tokens.clamp(16, 4_096);
+        }
+        config
+    }
+}
+
+/// A chat-completion backend
+pub trait LlmProvider: Send + Sync {
+    /// Provider name (e.g. "openrouter", "local")
+    fn name(&self) -> &str;
+
+    /// Active model and sampling settings
+    fn config(&self) -> &LlmConfig;
+
+    /// Same provider with different settings
+    fn with_config(&self, config: LlmConfig) -> Arc<dyn LlmProvider>;
+
+    /// Send a system + user prompt and return the raw completion text
+    fn complete<'a>(&'a self, system: &'a str, prompt: &'a str) -> BoxFuture<'a, Result<String>>;
+}
+
+/// Any OpenAI-compatible `/chat/completions` endpoint
+#[derive(Clone)]
+pub struct OpenAiCompatibleProvider {
+    name: String,
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    extra_headers: Vec<(&'static str, &'static str)>,
+    config: LlmConfig,
+}
+
+impl OpenAiCompatibleProvider {
+    /// Create a provider for an OpenAI-compatible base URL (without `/chat/completions`)
+    pub fn new(name: &str, base_url: &str, api_key: Option<String>, config: LlmConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|k| !k.is_empty()),
+            extra_headers: Vec::new(),
+            config,
+        }
+    }
+
+    /// OpenRouter (OpenAI-compatible, plus attribution headers)
+    pub fn openrouter(api_key: String, config: LlmConfig) -> Self {
+        let mut provider = Self::new("openrouter", OPENROUTER_BASE_URL, Some(api_key), config);
+        provider.extra_headers = vec![
+            ("HTTP-Referer", "https://orevault.app"),
+            ("X-Title", "OreVault Miner"),
+        ];
+        provider
+    }
+
+    async fn chat(&self, system: &str, prompt: &str) -> Result<String> {
+        let request_body = serde_json::json!({
+            "model": self.config.model,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": prompt }
+            ],
+            "max_tokens": self.config.max_tokens,
+            "temperature": self.config.temperature
+        });
+
+        let mut request = self.client
+            .post(format!("{}/chat/completions", self.base_url))
+            .timeout(self.config.timeout)
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", key));
+        }
+        for (name, value) in &self.extra_headers {
+            request = request.header(*name, *value);
+        }
+
+        let response = request.send().await
+            .with_context(|| format!("Failed to call {} API", self.name))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("{} API error {}: {}", self.name, status, body);
+        }
+
+        let json: serde_json::Value = response.json().await
+            .with_context(|| format!("Failed to parse {} response", self.name))?;
+
+        let content = json["choices"][0]["message"]["content"]
+            .as_str()
+            .with_context(|| format!("No content in {} response", self.name))?;
+
+        debug!("{} response ({}): {}", self.name, self.config.model, content);
+        Ok(content.to_string())
+    }
+}
+
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn config(&self) -> &LlmConfig {
+        &self.config
+    }
+
+    fn with_config(&self, config: LlmConfig) -> Arc<dyn LlmProvider> {
+        Arc::new(Self { config, ..self.clone() })
+    }
+
+    fn complete<'a>(&'a self, system: &'a str, prompt: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(self.chat(system, prompt))
+    }
+}
+
+/// Providers available to sessions, by name
+#[derive(Clone, Default)]
+pub struct LlmRegistry {
+    providers: HashMap<String, Arc<dyn LlmProvider>>,
+    default: Option<String>,
+}
+
+impl LlmRegistry {
+    /// Register a provider; the first one registered becomes the default
+    pub fn register(&mut self, provider: Arc<dyn LlmProvider>) {
+        let name = provider.name().to_string();
+        if self.default.is_none() {
+            self.default = Some(name.clone());
+        }
+        self.providers.insert(name, provider);
+    }
+
+    /// Make a registered provider the default
+    pub fn set_default(&mut self, name: &str) -> Result<()> {
+        if !self.providers.contains_key(name) {
+            anyhow::bail!("Unknown LLM provider: {}", name);
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    /// The default provider, if any are registered
+    pub fn default_provider(&self) -> Option<Arc<dyn LlmProvider>> {
+        self.default.as_ref().and_then(|name| self.providers.get(name).cloned())
+    }
+
+    /// Registered provider names
+    pub fn names(&self) -> Vec<String> {
+        self.providers.keys().cloned().collect()
+    }
+
+    /// Resolve a session's provider choice and overrides
+    pub fn resolve(&self, overrides: &LlmOverrides) -> Result<Arc<dyn LlmProvider>> {
+        let name = overrides.provider.as_ref().or(self.default.as_ref())
+            .context("No LLM providers configured")?;
+        let provider = self.providers.get(name)
+            .with_context(|| format!("Unknown LLM provider: {}", name))?;
+        Ok(provider.with_config(overrides.apply(provider.config().clone())))
+    }
+}
+
+/// Provider that replays canned responses, for tests
+#[cfg(test)]
+pub struct ScriptedProvider {
+    responses: parking_lot::Mutex<std::collections::VecDeque<Result<String, String>>>,
+    config: LlmConfig,
+}
+
+#[cfg(test)]
+impl ScriptedProvider {
+    pub fn new(responses: Vec<Result<String, String>>) -> Self {
+        Self {
+            responses: parking_lot::Mutex::new(responses.into()),
+            config: LlmConfig { model: "scripted".to_string(), ..Default::default() },
+        }
+    }
+}
+
+#[cfg(test)]
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn config(&self) -> &LlmConfig {
+        &self.config
+    }
+
+    fn with_config(&self, config: LlmConfig) -> Arc<dyn LlmProvider> {
+        Arc::new(Self {
+            responses: parking_lot::Mutex::new(self.responses.lock().clone()),
+            config,
+        })
+    }
+
+    fn complete<'a>(&'a self, _system: &'a str, _prompt: &'a str) -> BoxFuture<'a, Result<String>> {
+        let next = self.responses.lock().pop_front()
+            .unwrap_or_else(|| Err("script exhausted".to_string()));
+        Box::pin(async move { next.map_err(|e| anyhow::anyhow!(e)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_resolves_overrides() {
+        let mut registry = LlmRegistry::default();
+        registry.register(Arc::new(OpenAiCompatibleProvider::new(
+            "local", "http://127.0.0.1:8080/v1/", None, LlmConfig::default(),
+        )));
+
+        let provider = registry.resolve(&LlmOverrides {
+            model: Some("qwen2.5-7b".to_string()),
+            temperature: Some(5.0),
+            ..Default::default()
+        }).unwrap();
+
+        assert_eq!(provider.name(), "local");
+        assert_eq!(provider.config().model, "qwen2.5-7b");
+        assert_eq!(provider.config().temperature, 2.0);
+        assert!(registry.resolve(&LlmOverrides {
+            provider: Some("missing".to_string()),
+            ..Default::default()
+        }).is_err());
+    }
+}
@@ -4,11 +4,30 @@
 //! Handles WebSocket connections, REST API, and coordinates mining strategy.
 
 mod ai;
+mod allocation;
 mod balances;
 mod claims;
+mod control;
 mod db;
+mod diagnosis;
+mod ev;
+mod experiment;
+mod fleet;
 mod jito;
+mod llm;
+mod model;
+mod motherlode;
 mod ore;
+mod portfolio;
+mod price;
+mod rebroadcast;
+mod risk;
+mod rules;
+mod scheduler;
+mod script;
+mod sender;
+mod simulate;
+mod sizing;
 mod strategy;
 mod wallet;
 mod ws;
@@ -18,40 +37,57 @@ use std::net::SocketAddr;
 
 use anyhow::Result;
 use axum::{
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
     Json,
-    extract::{State, Query, WebSocketUpgrade},
+    extract::{State, Path, Query, WebSocketUpgrade},
     response::IntoResponse,
 };
 use tower_http::cors::{CorsLayer, Any};
 use tower_http::trace::TraceLayer;
 use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPoolOptions;
 
 use crate::ai::AiStrategy;
+use crate::control::ConfigUpdate;
 use crate::db::Database;
+use crate::experiment::{Assignment, Experiment, ExperimentArm};
+use crate::fleet::{Fleet, FleetMember, FleetSplit};
 use crate::ws::WebSocketManager;
 use crate::strategy::StrategyEngine;
 use crate::balances::BalanceManager;
 use crate::claims::ClaimsProcessor;
 use crate::ore::OreClient;
 use crate::jito::JitoClient;
+use crate::llm::{LlmConfig, LlmOverrides, LlmRegistry};
+use crate::price::{FeedPrice, PriceCache, PriceSource, StaticPrice};
+use crate::rebroadcast::Rebroadcaster;
+use crate::portfolio::{Portfolio, PortfolioConfig};
+use crate::risk::{RiskLimits, RiskManager};
+use crate::scheduler::{Run, Schedule, ScheduleAction, ScheduleSpec};
+use crate::script::{CompiledScript, ScriptLimits, ScriptRunner};
+use crate::sender::TxSender;
 use crate::wallet::WalletManager;
 
 /// Application state shared across all handlers
 pub struct AppState {
     pub db: Database,
     pub ws_manager: WebSocketManager,
-    pub strategy_engine: Arc<RwLock<StrategyEngine>>,
+    pub strategy_engine: Arc<StrategyEngine>,
     pub balance_manager: BalanceManager,
     pub claims_processor: ClaimsProcessor,
     pub ore_client: OreClient,
     pub jito_client: JitoClient,
+    pub tx_sender: TxSender,
+    pub rebroadcaster: Rebroadcaster,
     pub ai_strategy: AiStrategy,
+    pub llm_registry: LlmRegistry,
+    pub script_runner: ScriptRunner,
+    pub prices: PriceCache,
+    pub risk: RiskManager,
+    pub portfolio: Portfolio,
     pub wallet_manager: Arc<WalletManager>,
 }
 
@@ -75,8 +111,11 @@ async fn main() -> Result<()> {
         .expect("DATABASE_URL must be set");
     let rpc_url = std::env::var("RPC_URL")
         .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
-    let jito_block_engine = std::env::var("JITO_BLOCK_ENGINE")
-        .unwrap_or_else(|_| "ny.mainnet.block-engine.jito.wtf".to_string());
+    // Comma-separated regional block engines; JITO_BLOCK_ENGINE still works for a single region
+    let jito_block_engines: Vec<String> = std::env::var("JITO_BLOCK_ENGINES")
+        .or_else(|_| std::env::var("JITO_BLOCK_ENGINE"))
+        .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_else(|_| jito::DEFAULT_BLOCK_ENGINES.iter().map(|s| s.to_string()).collect());
     let server_port: u16 = std::env::var("PORT")
         .unwrap_or_else(|_| "3001".to_string())
         .parse()
@@ -97,19 +136,77 @@ async fn main() -> Result<()> {
     
     // Initialize components
     let db = Database::new(pool);
+    
+    // `train [path]`: fit the local block model from resolved rounds, save it and exit
+    let model_path = std::env::var("AI_MODEL_PATH")
+        .unwrap_or_else(|_| model::DEFAULT_MODEL_PATH.to_string());
+    if std::env::args().nth(1).as_deref() == Some("train") {
+        let output = std::env::args().nth(2).unwrap_or(model_path);
+        let block_model = model::train_from_db(&db, &model::TrainConfig::default()).await?;
+        block_model.save(&output)?;
+        info!("Saved local block model ({} examples) to {}", block_model.trained_examples, output);
+        return Ok(());
+    }
     let ws_manager = WebSocketManager::new();
     let ore_client = OreClient::new(&rpc_url)?;
-    let jito_client = JitoClient::new(&jito_block_engine).await?;
+    let jito_client = JitoClient::new(&jito_block_engines).await?;
     let balance_manager = BalanceManager::new(ore_client.clone());
     let claims_processor = ClaimsProcessor::new(ore_client.clone());
     
-    // Initialize AI strategy with OpenRouter API key (optional)
+    // Register LLM providers: OpenRouter (if keyed) and an optional OpenAI-compatible endpoint
+    let llm_config = LlmConfig {
+        model: std::env::var("AI_MODEL").unwrap_or_else(|_| llm::DEFAULT_MODEL.to_string()),
+        temperature: std::env::var("AI_TEMPERATURE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.3),
+        timeout: std::time::Duration::from_millis(
+            std::env::var("AI_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2_000),
+        ),
+        max_tokens: std::env::var("AI_MAX_TOKENS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300),
+    };
+    let mut llm_registry = LlmRegistry::default();
     let openrouter_api_key = std::env::var("OPENROUTER_API_KEY").unwrap_or_default();
-    let ai_strategy = AiStrategy::new(openrouter_api_key.clone());
+    if !openrouter_api_key.is_empty() {
+        llm_registry.register(Arc::new(llm::OpenAiCompatibleProvider::openrouter(openrouter_api_key, llm_config.clone())));
+    }
+    if let Ok(base_url) = std::env::var("AI_BASE_URL") {
+        let local_config = LlmConfig {
+            model: std::env::var("AI_LOCAL_MODEL").unwrap_or_else(|_| llm_config.model.clone()),
+            ..llm_config.clone()
+        };
+        llm_registry.register(Arc::new(llm::OpenAiCompatibleProvider::new(
+            "local",
+            &base_url,
+            std::env::var("AI_API_KEY").ok(),
+            local_config,
+        )));
+    }
+    if let Ok(name) = std::env::var("AI_PROVIDER") {
+        if let Err(e) = llm_registry.set_default(&name) {
+            warn!("{}", e);
+        }
+    }
+    let mut ai_strategy = AiStrategy::new(llm_registry.default_provider()).with_audit(db.clone());
+    if std::path::Path::new(&model_path).exists() {
+        match model::BlockModel::load(&model_path) {
+            Ok(block_model) => {
+                info!("Loaded local block model from {} (trained {})", model_path, block_model.trained_at);
+                ai_strategy = ai_strategy.with_model(Arc::new(block_model));
+            }
+            Err(e) => warn!("Ignoring local block model: {}", e),
+        }
+    }
     if ai_strategy.is_configured() {
-        info!("AI Strategy enabled with Gemini 2.0 Flash (~750ms decisions)");
+        info!("AI Strategy enabled with {} (providers: {:?})", ai_strategy.model(), llm_registry.names());
     } else {
-        info!("AI Strategy running in fallback mode (no API key)");
+        info!("AI Strategy running in fallback mode (no provider configured)");
     }
     
     // Initialize wallet manager with database persistence (Supabase)
@@ -123,11 +220,145 @@ async fn main() -> Result<()> {
     
     // Create strategy engine and wire in AI + wallet manager
     let mut strategy_engine_inner = StrategyEngine::new(ore_client.clone(), jito_client.clone());
-    if !openrouter_api_key.is_empty() {
+    if ai_strategy.is_configured() {
         strategy_engine_inner.set_ai_strategy(ai_strategy.clone());
     }
     strategy_engine_inner.set_wallet_manager(wallet_manager.clone());
-    let strategy_engine = Arc::new(RwLock::new(strategy_engine_inner));
+    strategy_engine_inner.set_database(db.clone());
+    
+    // Session strategy scripts, each run bounded by an operation count and a time limit
+    let script_runner = ScriptRunner::new(ScriptLimits {
+        max_operations: std::env::var("SCRIPT_MAX_OPERATIONS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(500_000),
+        timeout: std::time::Duration::from_millis(
+            std::env::var("SCRIPT_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50),
+        ),
+    });
+    strategy_engine_inner.set_script_runner(script_runner.clone());
+    
+    // ORE/SOL price for valuing ORE rewards: a feed (file or URL) or a static value, else zero
+    let price_source: Arc<dyn PriceSource> = match std::env::var("ORE_PRICE_FEED") {
+        Ok(location) if !location.is_empty() => Arc::new(FeedPrice::new(&location)),
+        _ => Arc::new(StaticPrice(
+            std::env::var("ORE_PRICE_SOL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+        )),
+    };
+    info!("ORE price source: {}", price_source.name());
+    let prices = PriceCache::new(
+        price_source,
+        std::time::Duration::from_secs(
+            std::env::var("ORE_PRICE_REFRESH_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+        ),
+        std::time::Duration::from_secs(
+            std::env::var("ORE_PRICE_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(600),
+        ),
+    );
+    strategy_engine_inner.set_price_cache(prices.clone());
+    
+    // Risk limits on top of session budgets; the kill switch and cooldowns persist across restarts
+    let sol_limit = |name: &str| std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v > 0.0)
+        .map(|v| (v * 1_000_000_000.0) as u64);
+    let risk_limits = RiskLimits {
+        max_daily_loss: sol_limit("RISK_MAX_DAILY_LOSS_SOL"),
+        loss_streak: std::env::var("RISK_LOSS_STREAK").ok().and_then(|v| v.parse().ok()),
+        cooldown_rounds: std::env::var("RISK_COOLDOWN_ROUNDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10),
+        max_drawdown: sol_limit("RISK_MAX_DRAWDOWN_SOL"),
+    };
+    info!("Risk limits: {:?}", risk_limits);
+    let risk = RiskManager::load(db.clone(), risk_limits).await?;
+    strategy_engine_inner.set_risk_manager(risk.clone());
+    
+    // Sessions mining at once are planned together each round (off | deconflict | spread)
+    let portfolio_config = PortfolioConfig {
+        mode: std::env::var("PORTFOLIO_MODE")
+            .ok()
+            .and_then(|m| m.parse().ok())
+            .unwrap_or_default(),
+        max_round_exposure: sol_limit("PORTFOLIO_MAX_ROUND_SOL"),
+        wait: std::time::Duration::from_millis(
+            std::env::var("PORTFOLIO_WAIT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+        ),
+    };
+    info!("Portfolio coordination: {:?}", portfolio_config);
+    let active = portfolio_config.is_active();
+    let portfolio = Portfolio::new(portfolio_config);
+    if active {
+        strategy_engine_inner.set_portfolio(portfolio.clone());
+    }
+    
+    // Pre-submission simulation (rpc | bundle | off), skipped when too few slots remain
+    let simulation_config = simulate::SimulationConfig {
+        mode: std::env::var("SIMULATION_MODE")
+            .ok()
+            .and_then(|m| m.parse().ok())
+            .unwrap_or_default(),
+        min_slots: std::env::var("SIMULATION_MIN_SLOTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3),
+        timeout: std::time::Duration::from_millis(
+            std::env::var("SIMULATION_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(400),
+        ),
+    };
+    info!("Deploy simulation: {:?}", simulation_config);
+    strategy_engine_inner.set_simulation_config(simulation_config);
+    
+    // Submission paths raced for each deploy (rpc, jito, tpu)
+    let submission_policy = sender::SubmissionPolicy {
+        paths: std::env::var("SEND_PATHS")
+            .map(|v| v.split(',').filter_map(|p| p.parse().ok()).collect())
+            .unwrap_or_else(|_| vec![sender::PathKind::Rpc]),
+        extra_rpc_urls: std::env::var("SEND_RPC_URLS")
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default(),
+        websocket_url: std::env::var("WS_URL").ok(),
+        tpu_fanout_slots: std::env::var("TPU_FANOUT_SLOTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2),
+    };
+    let tx_sender = TxSender::new(ore_client.clone(), jito_client.clone(), &rpc_url, submission_policy).await;
+    strategy_engine_inner.set_sender(tx_sender.clone());
+    
+    // Deploys are resent until they confirm, their blockhash expires or the round closes
+    let rebroadcast_config = rebroadcast::RebroadcastConfig {
+        interval: std::time::Duration::from_millis(
+            std::env::var("REBROADCAST_INTERVAL_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+        ),
+        ..Default::default()
+    };
+    let rebroadcaster = Rebroadcaster::new(ore_client.clone(), tx_sender.clone(), rebroadcast_config);
+    strategy_engine_inner.set_rebroadcaster(rebroadcaster.clone());
+    let strategy_engine = Arc::new(strategy_engine_inner);
     
     // Create shared application state
     let state = Arc::new(AppState {
@@ -138,21 +369,62 @@ async fn main() -> Result<()> {
         claims_processor,
         ore_client,
         jito_client,
+        tx_sender,
+        rebroadcaster,
         ai_strategy,
+        llm_registry,
+        script_runner,
+        prices,
+        risk,
+        portfolio,
         wallet_manager,
     });
     
+    // Scheduled sessions are started and stopped from a background task.
+    // Cron schedules only match the current minute, so a tick never exceeds 60s
+    let scheduler_tick = std::time::Duration::from_secs(
+        std::env::var("SCHEDULER_TICK_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(15)
+            .min(60),
+    );
+    tokio::spawn(run_scheduler(state.clone(), scheduler_tick));
+    
     // Build router with all API routes
     let app = Router::new()
         // Mining endpoints
         .route("/api/session/start", post(start_session))
         .route("/api/session/stop", post(stop_session))
+        .route("/api/session", patch(update_session))
+        .route("/api/session/pause", post(pause_session))
+        .route("/api/session/resume", post(resume_session))
+        .route("/api/session/changes", get(get_session_changes))
+        .route("/api/session/:id/status", get(get_session_status))
+        .route("/api/sessions/active", get(get_active_sessions))
+        .route("/api/session/script", get(get_session_script).put(set_session_script).delete(delete_session_script))
+        .route("/api/session/skips", get(get_round_skips))
+        .route("/api/fleet/stats", get(get_fleet_stats))
+        .route("/api/schedules", get(list_schedules).post(create_schedule))
+        .route("/api/schedules/:id", get(get_schedule).put(update_schedule).delete(delete_schedule))
+        .route("/api/scripts/validate", post(validate_script))
         .route("/api/stats", get(get_stats))
         .route("/api/transactions", get(get_transactions))
         // Grid & Round endpoints
         .route("/api/grid", get(get_grid))
         .route("/api/round", get(get_round))
         .route("/api/ai/suggest", post(get_ai_suggestion))
+        .route("/api/ai/decisions", get(get_ai_decisions))
+        .route("/api/experiments", get(list_experiments).post(create_experiment))
+        .route("/api/experiments/:id", get(get_experiment))
+        .route("/api/experiments/:id/end", post(end_experiment))
+        .route("/api/portfolio", get(get_portfolio))
+        .route("/api/risk", get(get_risk))
+        .route("/api/risk/kill", post(engage_kill_switch))
+        .route("/api/risk/reset", post(reset_kill_switch))
+        .route("/api/jito/regions", get(get_jito_regions))
+        .route("/api/submission/paths", get(get_submission_paths))
         // Balance & Claims endpoints
         .route("/api/balances", get(get_balances))
         .route("/api/balances/sync", post(sync_balances))
@@ -165,6 +437,7 @@ async fn main() -> Result<()> {
         .route("/api/wallet/import", post(import_wallet))
         .route("/api/wallet/list", get(list_wallets))
         .route("/api/wallet/export", post(export_wallet))
+        .route("/api/wallet/tags", post(set_wallet_tags))
         // WebSocket endpoint
         .route("/ws", get(ws_handler))
         // Health check
@@ -180,176 +453,1290 @@ async fn main() -> Result<()> {
     let addr = SocketAddr::from(([0, 0, 0, 0], server_port));
     info!("OreVault API listening on {}", addr);
     
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// API Handlers
+// =============================================================================
+
+/// Health check endpoint
+async fn health_check() -> impl IntoResponse {
+    Json(serde_json::json!({
+        "status": "healthy",
+        "version": "1.0.0",
+        "network": "mainnet-beta"
+    }))
+}
+
+/// Start mining session request
+#[derive(Debug, Deserialize)]
+pub struct StartSessionRequest {
+    pub wallet: String,
+    pub strategy: Strategy,
+    pub deploy_amount: f64,
+    pub max_tip: f64,
+    pub budget: f64,
+    #[serde(default = "default_num_blocks")]
+    pub num_blocks: u8,
+    /// LLM provider choice and overrides for this session
+    #[serde(default)]
+    pub ai: Option<LlmOverrides>,
+    /// Enroll the session in an experiment; its arms override the config above
+    #[serde(default)]
+    pub experiment_id: Option<uuid::Uuid>,
+    /// Rhai strategy script; replaces AI selection for this session
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Per-round stake sizing (defaults to the fixed `deploy_amount`)
+    #[serde(default)]
+    pub sizing: sizing::SizingPolicy,
+    /// Same amount on every square (`uniform`) or more on thin squares (`thin`)
+    #[serde(default)]
+    pub allocation: allocation::AllocationMode,
+    /// Play only for the motherlode; sets its own stake and square count
+    #[serde(default)]
+    pub motherlode: Option<motherlode::MotherlodeMode>,
+    /// Skip/play rules judged every round (see `rules`)
+    #[serde(default)]
+    pub rules: rules::RuleSet,
+    /// Mine with a fleet of managed wallets, led by `wallet`
+    #[serde(default)]
+    pub fleet: Option<FleetRequest>,
+}
+
+/// Wallets a fleet session mines with, besides the lead
+#[derive(Debug, Deserialize)]
+pub struct FleetRequest {
+    #[serde(default)]
+    pub wallets: Vec<String>,
+    /// All managed wallets with this tag
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// How each round's deploy is split across the wallets
+    #[serde(default)]
+    pub split: FleetSplit,
+}
+
+fn default_num_blocks() -> u8 { 1 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    BestEv,
+    Conservative,
+    Aggressive,
+}
+
+impl Strategy {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::BestEv => "best_ev",
+            Strategy::Conservative => "conservative",
+            Strategy::Aggressive => "aggressive",
+        }
+    }
+}
+
+/// Start a mining session
+async fn start_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<StartSessionRequest>,
+) -> impl IntoResponse {
+    match launch_session(&state, req).await {
+        Ok(started) => Json(serde_json::json!({
+            "success": true,
+            "session_id": started.session_id,
+            "fleet_id": started.fleet_id
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// A session started by `launch_session`
+pub struct StartedSession {
+    pub session_id: uuid::Uuid,
+    pub fleet_id: Option<uuid::Uuid>,
+}
+
+/// Validate a session request, create its session rows and start it in the
+/// engine. Used by the API and the scheduler
+async fn launch_session(state: &AppState, req: StartSessionRequest) -> Result<StartedSession> {
+    // Basic input validation (safety): prevent accidental catastrophic SOL amounts.
+    // These values come from user input (frontend) and are interpreted as SOL.
+    // Minimum 0.0001 SOL (100,000 lamports) per square
+    if !req.deploy_amount.is_finite() || req.deploy_amount < 0.0001 || req.deploy_amount > 10.0 {
+        anyhow::bail!("deploy_amount must be >= 0.0001 and <= 10 (SOL)");
+    }
+    
+    // Log the received values for debugging
+    tracing::info!("Session request: wallet={}, deploy_amount={} SOL ({} lamports), num_blocks={}",
+        req.wallet,
+        req.deploy_amount,
+        (req.deploy_amount * 1_000_000_000.0) as u64,
+        req.num_blocks
+    );
+    if !req.max_tip.is_finite() || req.max_tip < 0.0 || req.max_tip > 1.0 {
+        anyhow::bail!("max_tip must be >= 0 and <= 1 (SOL)");
+    }
+    if !req.budget.is_finite() || req.budget <= 0.0 {
+        anyhow::bail!("budget must be > 0 (SOL)");
+    }
+    req.sizing.validate()?;
+    if let Some(mode) = &req.motherlode {
+        mode.validate()?;
+    }
+    req.rules.validate()?;
+    let num_blocks = req.num_blocks.clamp(1, 25);
+    if state.strategy_engine.is_mining(&req.wallet) {
+        anyhow::bail!("Wallet is already mining");
+    }
+    
+    // Resolve the session's LLM provider (None = engine default)
+    let session_ai = match &req.ai {
+        Some(overrides) => Some(state.ai_strategy.with_provider(state.llm_registry.resolve(overrides)?)),
+        None => None,
+    };
+    
+    // Load the experiment the session is enrolled in, if any
+    let experiment = match req.experiment_id {
+        Some(id) => match state.db.get_experiment(id).await? {
+            Some(record) => {
+                let experiment = Experiment::from_record(record)?;
+                if experiment.status != "active" {
+                    anyhow::bail!("Experiment {} has ended", id);
+                }
+                if !experiment.enrolls(&req.wallet) {
+                    anyhow::bail!("Wallet is not assigned an arm in experiment {}", id);
+                }
+                Some(Arc::new(experiment))
+            }
+            None => anyhow::bail!("Experiment {} not found", id),
+        },
+        None => None,
+    };
+    
+    // Reject a broken script before the session is created
+    let script = match req.script.clone() {
+        Some(source) => Some(check_script(&state.script_runner, source).await?),
+        None => None,
+    };
+    
+    // A fleet's wallets must be managed and not already mining
+    let fleet_wallets = match &req.fleet {
+        Some(fleet) => Some(resolve_fleet_wallets(state, &req.wallet, fleet).await?),
+        None => None,
+    };
+
+    // Verify wallet signature for authentication
+    // In production, verify the signature against a known message
+    
+    // Convert f64 to lamports (i64)
+    let max_tip_lamports = (req.max_tip * 1_000_000_000.0) as i64;
+    let deploy_lamports = (req.deploy_amount * 1_000_000_000.0) as i64;
+    let budget_lamports = (req.budget * 1_000_000_000.0) as i64;
+    
+    let session = state.db.create_session(
+        &req.wallet,
+        req.strategy.clone(),
+        max_tip_lamports,
+        deploy_lamports,
+        budget_lamports,
+    ).await?;
+    
+    // Start the strategy engine for this wallet
+    let config = strategy::SessionConfig::new(
+        session.id,
+        req.wallet.clone(),
+        req.strategy.clone(),
+        req.deploy_amount,
+        req.max_tip,
+        num_blocks,
+    )
+    .with_sizing(req.sizing, req.budget)
+    .with_allocation(req.allocation)
+    .with_rules(req.rules);
+    let config = match req.motherlode {
+        Some(mode) => config.with_motherlode(mode),
+        None => config,
+    };
+    let config = match experiment {
+        Some(experiment) => config.with_experiment(experiment),
+        None => config,
+    };
+    let config = match fleet_wallets {
+        Some(wallets) => {
+            let split = req.fleet.as_ref().map(|f| f.split).unwrap_or_default();
+            let amounts = (max_tip_lamports, deploy_lamports, budget_lamports);
+            match create_fleet_sessions(&state.db, &req.wallet, &req.strategy, session.id, wallets, split, amounts).await {
+                Ok(fleet) => config.with_fleet(fleet),
+                Err(e) => {
+                    let _ = state.db.end_session(&req.wallet).await;
+                    return Err(e);
+                }
+            }
+        }
+        None => config,
+    };
+    let fleet_id = config.fleet.as_ref().map(|f| f.id);
+    let started = match script {
+        Some(script) => install_script(state, session.id, script).await.map(|_| ()),
+        None => Ok(()),
+    }
+    .and_then(|()| state.strategy_engine.start_session(config, session_ai));
+    if let Err(e) = started {
+        state.script_runner.remove(session.id);
+        let _ = match fleet_id {
+            Some(fleet_id) => state.db.end_fleet(fleet_id).await,
+            None => state.db.end_session(&req.wallet).await,
+        };
+        return Err(e);
+    }
+    
+    info!("Started session {} for wallet {}", session.id, req.wallet);
+    Ok(StartedSession { session_id: session.id, fleet_id })
+}
+
+/// The lead first, then the fleet's named and tagged wallets
+async fn resolve_fleet_wallets(state: &AppState, lead: &str, fleet: &FleetRequest) -> Result<Vec<String>> {
+    let mut named = fleet.wallets.clone();
+    if let Some(tag) = &fleet.tag {
+        named.extend(state.db.get_wallets_with_tag(tag).await?);
+    }
+    let mut wallets = vec![lead.to_string()];
+    for wallet in named {
+        if !wallets.contains(&wallet) {
+            wallets.push(wallet);
+        }
+    }
+    if wallets.len() < 2 {
+        anyhow::bail!("A fleet needs at least one wallet besides the lead");
+    }
+    
+    for wallet in &wallets {
+        if !state.wallet_manager.has_keypair(wallet).await {
+            anyhow::bail!("Fleet wallet {} is not a managed wallet", wallet);
+        }
+        if state.strategy_engine.is_mining(wallet) {
+            anyhow::bail!("Wallet {} is already mining", wallet);
+        }
+    }
+    Ok(wallets)
+}
+
+/// Create the fleet and a session per wallet, the lead's being `lead_session`.
+/// Amounts are (max_tip, deploy_amount, budget) in lamports
+async fn create_fleet_sessions(
+    db: &Database,
+    lead: &str,
+    strategy: &Strategy,
+    lead_session: uuid::Uuid,
+    wallets: Vec<String>,
+    split: FleetSplit,
+    (max_tip, deploy_amount, budget): (i64, i64, i64),
+) -> Result<Fleet> {
+    let record = db.create_fleet(lead, split.as_str()).await?;
+    let mut members = Vec::with_capacity(wallets.len());
+    for wallet in wallets {
+        let session_id = if wallet == lead {
+            lead_session
+        } else {
+            match db.create_session(&wallet, strategy.clone(), max_tip, deploy_amount, budget).await {
+                Ok(session) => session.id,
+                Err(e) => {
+                    let _ = db.end_fleet(record.id).await;
+                    return Err(e);
+                }
+            }
+        };
+        db.add_fleet_session(record.id, session_id).await?;
+        members.push(FleetMember { wallet, session_id });
+    }
+    Ok(Fleet { id: record.id, split, members })
+}
+
+/// Stop mining session request
+#[derive(Debug, Deserialize)]
+pub struct StopSessionRequest {
+    pub wallet: String,
+}
+
+/// Stop a mining session
+async fn stop_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<StopSessionRequest>,
+) -> impl IntoResponse {
+    match halt_session(&state, &req.wallet).await {
+        Ok(()) => Json(serde_json::json!({
+            "success": true
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Stop a wallet's session in the engine and end its session rows.
+/// Used by the API and the scheduler
+async fn halt_session(state: &AppState, wallet: &str) -> Result<()> {
+    state.strategy_engine.stop_session(wallet).await;
+    
+    // Stopping a fleet's lead ends every wallet's session
+    match state.db.get_active_fleet(wallet).await? {
+        Some(fleet) => state.db.end_fleet(fleet.id).await?,
+        None => state.db.end_session(wallet).await?,
+    }
+    info!("Stopped session for wallet {}", wallet);
+    Ok(())
+}
+
+/// Pause or resume session request
+#[derive(Debug, Deserialize)]
+pub struct PauseSessionRequest {
+    pub wallet: String,
+}
+
+/// Pause a running session from its next round; it keeps its session and counters
+async fn pause_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PauseSessionRequest>,
+) -> impl IntoResponse {
+    match set_session_paused(&state, &req.wallet, true).await {
+        Ok(()) => Json(serde_json::json!({
+            "success": true
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Resume a paused session
+async fn resume_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PauseSessionRequest>,
+) -> impl IntoResponse {
+    match set_session_paused(&state, &req.wallet, false).await {
+        Ok(()) => Json(serde_json::json!({
+            "success": true
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Pause or resume a session in the engine and record it in the audit trail
+async fn set_session_paused(state: &AppState, wallet: &str, paused: bool) -> Result<()> {
+    let session_id = state.strategy_engine.set_paused(wallet, paused).await?;
+    let action = if paused { "pause" } else { "resume" };
+    state.db.record_session_change(session_id, wallet, action, &serde_json::json!({})).await?;
+    Ok(())
+}
+
+/// Live session change request (SOL amounts); omitted fields stay as they are
+#[derive(Debug, Deserialize)]
+pub struct UpdateSessionRequest {
+    pub wallet: String,
+    #[serde(default)]
+    pub deploy_amount: Option<f64>,
+    #[serde(default)]
+    pub max_tip: Option<f64>,
+    #[serde(default)]
+    pub num_blocks: Option<u8>,
+    #[serde(default)]
+    pub strategy: Option<Strategy>,
+}
+
+/// Change a running session's config from its next round
+async fn update_session(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateSessionRequest>,
+) -> impl IntoResponse {
+    match reconfigure_session(&state, req).await {
+        Ok(changes) => Json(serde_json::json!({
+            "success": true,
+            "changes": changes
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Validate a config change, send it to the running session and record it
+async fn reconfigure_session(state: &AppState, req: UpdateSessionRequest) -> Result<serde_json::Value> {
+    // Same limits as when the session was started
+    if let Some(amount) = req.deploy_amount {
+        if !amount.is_finite() || !(0.0001..=10.0).contains(&amount) {
+            anyhow::bail!("deploy_amount must be >= 0.0001 and <= 10 (SOL)");
+        }
+    }
+    if let Some(tip) = req.max_tip {
+        if !tip.is_finite() || !(0.0..=1.0).contains(&tip) {
+            anyhow::bail!("max_tip must be >= 0 and <= 1 (SOL)");
+        }
+    }
+    let update = ConfigUpdate {
+        deploy_amount: req.deploy_amount.map(|sol| (sol * 1_000_000_000.0) as u64),
+        max_tip: req.max_tip.map(|sol| (sol * 1_000_000_000.0) as u64),
+        num_blocks: req.num_blocks,
+        strategy: req.strategy,
+    };
+    if update.is_empty() {
+        anyhow::bail!("Nothing to change: set deploy_amount, max_tip, num_blocks or strategy");
+    }
+    
+    let (config, changes) = state.strategy_engine.update_session(&req.wallet, update).await?;
+    // A change that can't be audited doesn't stay
+    if let Err(e) = state.db.record_session_change(config.session_id, &req.wallet, "update", &changes).await {
+        if let Err(undo) = state.strategy_engine.update_session(&req.wallet, ConfigUpdate::undo(&changes)).await {
+            warn!("Failed to undo unaudited change to {}'s session: {}", req.wallet, undo);
+        }
+        return Err(e);
+    }
+    // Every wallet of a fleet runs with the lead's config
+    for member in config.members() {
+        state.db.update_session_config(
+            member.session_id,
+            member.strategy.clone(),
+            member.max_tip as i64,
+            member.deploy_amount as i64,
+        ).await?;
+    }
+    Ok(changes)
+}
+
+/// Pauses, resumes and config changes made to the wallet's current session
+async fn get_session_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let changes = match state.db.get_active_session(&query.wallet).await {
+        Ok(Some(session)) => state.db.get_session_changes(session.id, 100).await,
+        Ok(None) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    };
+    match changes {
+        Ok(changes) => Json(serde_json::json!({
+            "success": true,
+            "changes": changes
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Seconds until the current round ends; None between rounds
+async fn next_round_eta(ore_client: &OreClient) -> Option<f64> {
+    let board = ore_client.get_board_state().await.ok()?;
+    if board.end_slot == u64::MAX {
+        return None;
+    }
+    let slot = ore_client.get_slot().await.ok()?;
+    Some(board.end_slot.saturating_sub(slot) as f64 * 0.4)
+}
+
+/// Fill in the parts of a session's status that come from the chain
+async fn live_status(state: &AppState, mut status: control::SessionStatus, eta: Option<f64>) -> control::SessionStatus {
+    if let Ok(authority) = status.wallet.parse::<solana_sdk::pubkey::Pubkey>() {
+        status.automation_balance = state.ore_client.get_automation_balance(&authority).await.ok();
+    }
+    status.next_round_eta_secs = eta;
+    status
+}
+
+/// Live status of every running session
+async fn get_active_sessions(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let statuses = state.strategy_engine.active_statuses().await;
+    let eta = next_round_eta(&state.ore_client).await;
+    let sessions = futures::future::join_all(
+        statuses.into_iter().map(|status| live_status(&state, status, eta))
+    ).await;
+    Json(serde_json::json!({
+        "success": true,
+        "sessions": sessions
+    }))
+}
+
+/// Live status of a running session: phase, last decision and signature,
+/// counters, automation balance and error streak
+async fn get_session_status(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.strategy_engine.status_by_id(id).await {
+        Ok(status) => {
+            let eta = next_round_eta(&state.ore_client).await;
+            Json(serde_json::json!({
+                "success": true,
+                "status": live_status(&state, status, eta).await
+            }))
+        }
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Query parameters for stats
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    pub wallet: String,
+}
+
+/// Get session statistics
+async fn get_stats(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    // First get the active session for the wallet
+    match state.db.get_active_session(&query.wallet).await {
+        Ok(Some(session)) => {
+            match state.db.get_session_stats(session.id).await {
+                Ok(stats) => {
+                    // ORE earned, net of the claim fee, at the cached ORE/SOL price; both
+                    // sides of the P&L come from settled deploys only
+                    let params = ev::EvParams { ore_price: state.prices.ore_price_lamports().await, ..Default::default() };
+                    let ore_value = params.ore_value(params.after_claim_fee(stats.ore_earned.max(0) as u64)) as i64;
+                    Json(serde_json::json!({
+                        "success": true,
+                        "ore_price": params.ore_price as f64 / 1_000_000_000.0,
+                        "price_source": state.prices.source_name(),
+                        "ore_value": ore_value,
+                        "net_pnl_with_ore": stats.settled_pnl + ore_value,
+                        "stats": stats
+                    }))
+                }
+                Err(e) => Json(serde_json::json!({
+                    "success": false,
+                    "error": e.to_string()
+                }))
+            }
+        }
+        Ok(None) => Json(serde_json::json!({
+            "success": false,
+            "error": "No active session found"
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Query parameters for transactions
+#[derive(Debug, Deserialize)]
+pub struct TransactionsQuery {
+    pub wallet: String,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Get transaction history
+async fn get_transactions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TransactionsQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+    
+    match state.db.get_transactions(&query.wallet, limit, offset).await {
+        Ok(transactions) => Json(serde_json::json!({
+            "success": true,
+            "transactions": transactions
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Query parameters for the AI decision log
+#[derive(Debug, Deserialize)]
+pub struct AiDecisionsQuery {
+    pub model: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Get the AI decision audit log and per-model accuracy
+async fn get_ai_decisions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AiDecisionsQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0).max(0);
+    
+    let decisions = match state.db.get_ai_decisions(query.model.as_deref(), limit, offset).await {
+        Ok(decisions) => decisions,
+        Err(e) => return Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    };
+    
+    match state.db.get_ai_accuracy().await {
+        Ok(accuracy) => Json(serde_json::json!({
+            "success": true,
+            "decisions": decisions,
+            "accuracy": accuracy
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Create experiment request
+#[derive(Debug, Deserialize)]
+pub struct CreateExperimentRequest {
+    pub name: String,
+    pub assignment: Assignment,
+    pub arms: Vec<ExperimentArm>,
+}
+
+/// Create a strategy experiment
+async fn create_experiment(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateExperimentRequest>,
+) -> impl IntoResponse {
+    let experiment = Experiment {
+        id: uuid::Uuid::new_v4(),
+        name: req.name,
+        assignment: req.assignment,
+        arms: req.arms,
+        status: "active".to_string(),
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = experiment.validate() {
+        return Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }));
+    }
+    
+    let saved = state.db.create_experiment(
+        experiment.id,
+        &experiment.name,
+        &serde_json::to_value(&experiment.assignment).unwrap_or_default(),
+        &serde_json::to_value(&experiment.arms).unwrap_or_default(),
+    ).await;
+    match saved {
+        Ok(_) => Json(serde_json::json!({
+            "success": true,
+            "experiment": experiment
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// List experiments
+async fn list_experiments(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match state.db.list_experiments().await {
+        Ok(records) => {
+            let experiments: Vec<Experiment> = records.into_iter()
+                .filter_map(|record| Experiment::from_record(record).ok())
+                .collect();
+            Json(serde_json::json!({
+                "success": true,
+                "experiments": experiments
+            }))
+        }
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Per-arm P&L, win rate and confidence intervals for an experiment
+async fn get_experiment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    let experiment = match state.db.get_experiment(id).await {
+        Ok(Some(record)) => match Experiment::from_record(record) {
+            Ok(experiment) => experiment,
+            Err(e) => return Json(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        },
+        Ok(None) => return Json(serde_json::json!({
+            "success": false,
+            "error": format!("Experiment {} not found", id)
+        })),
+        Err(e) => return Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    };
+    
+    match state.db.get_experiment_transactions(id).await {
+        Ok(rows) => Json(serde_json::json!({
+            "success": true,
+            "arms": crate::experiment::report(&experiment, &rows),
+            "experiment": experiment
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// End an experiment
+async fn end_experiment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.db.end_experiment(id).await {
+        Ok(()) => Json(serde_json::json!({
+            "success": true
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Compile a script and dry-run it on a sample grid, off the async runtime
+async fn check_script(runner: &ScriptRunner, source: String) -> Result<CompiledScript> {
+    let runner = runner.clone();
+    tokio::task::spawn_blocking(move || {
+        runner.validate(&source)?;
+        runner.compile(&source, 0)
+    })
+    .await?
+}
+
+/// Save a checked script and hand it to the session's mining loop (used from the next round)
+async fn install_script(state: &AppState, session_id: uuid::Uuid, mut script: CompiledScript) -> Result<i32> {
+    let saved = state.db.save_session_script(session_id, &script.source).await?;
+    script.version = saved.version;
+    state.script_runner.set(session_id, script);
+    Ok(saved.version)
+}
+
+/// Script validation request
+#[derive(Debug, Deserialize)]
+pub struct ValidateScriptRequest {
+    pub source: String,
+}
+
+/// Compile a script and dry-run it on a sample grid
+async fn validate_script(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ValidateScriptRequest>,
+) -> impl IntoResponse {
+    let runner = state.script_runner.clone();
+    match tokio::task::spawn_blocking(move || runner.validate(&req.source)).await {
+        Ok(Ok(run)) => Json(serde_json::json!({
+            "success": true,
+            "decision": run.decision,
+            "operations": run.operations,
+            "elapsed_us": run.elapsed_us
+        })),
+        Ok(Err(e)) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Session script update request
+#[derive(Debug, Deserialize)]
+pub struct SessionScriptRequest {
+    pub wallet: String,
+    pub source: String,
+}
+
+/// Get the script of a wallet's active session
+async fn get_session_script(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let script = match state.db.get_active_session(&query.wallet).await {
+        Ok(Some(session)) => state.db.get_session_script(session.id).await,
+        Ok(None) => Ok(None),
+        Err(e) => Err(e),
+    };
+    match script {
+        Ok(script) => Json(serde_json::json!({
+            "success": true,
+            "script": script
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Replace the script of a wallet's running session (takes effect next round)
+async fn set_session_script(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SessionScriptRequest>,
+) -> impl IntoResponse {
+    let Some(session_id) = state.strategy_engine.session_id(&req.wallet) else {
+        return Json(serde_json::json!({
+            "success": false,
+            "error": "No running session for this wallet"
+        }));
+    };
+    let result = match check_script(&state.script_runner, req.source).await {
+        Ok(script) => install_script(&state, session_id, script).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(version) => {
+            info!("Updated script for session {} (v{})", session_id, version);
+            Json(serde_json::json!({
+                "success": true,
+                "session_id": session_id,
+                "version": version
+            }))
+        }
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Remove the script of a wallet's running session (AI selection resumes next round)
+async fn delete_session_script(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let Some(session_id) = state.strategy_engine.session_id(&query.wallet) else {
+        return Json(serde_json::json!({
+            "success": false,
+            "error": "No running session for this wallet"
+        }));
+    };
+    state.script_runner.remove(session_id);
+    match state.db.delete_session_script(session_id).await {
+        Ok(removed) => Json(serde_json::json!({
+            "success": true,
+            "removed": removed
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// P&L of the fleet led by the wallet, combined and per wallet
+async fn get_fleet_stats(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let fleet = match state.db.get_active_fleet(&query.wallet).await {
+        Ok(Some(fleet)) => fleet,
+        Ok(None) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "error": "No active fleet found"
+            }));
+        }
+        Err(e) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }));
+        }
+    };
+    let sessions = match state.db.get_fleet_sessions(fleet.id).await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }));
+        }
+    };
+    
+    let mut wallets = Vec::with_capacity(sessions.len());
+    let mut all = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        match state.db.get_session_stats(session.id).await {
+            Ok(stats) => {
+                wallets.push(serde_json::json!({
+                    "wallet": session.user_wallet,
+                    "session_id": session.id,
+                    "stats": stats
+                }));
+                all.push(stats);
+            }
+            Err(e) => {
+                return Json(serde_json::json!({
+                    "success": false,
+                    "error": e.to_string()
+                }));
+            }
+        }
+    }
+    Json(serde_json::json!({
+        "success": true,
+        "fleet_id": fleet.id,
+        "split": fleet.split,
+        "stats": db::SessionStats::combine(&all),
+        "wallets": wallets
+    }))
+}
+
+/// Rounds the wallet's current session sat out, with the motherlode at each skip
+async fn get_round_skips(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let skips = match state.db.get_active_session(&query.wallet).await {
+        Ok(Some(session)) => state.db.get_round_skips(session.id, 100).await,
+        Ok(None) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    };
+    match skips {
+        Ok(skips) => Json(serde_json::json!({
+            "success": true,
+            "skips": skips.iter().map(|s| serde_json::json!({
+                "round_id": s.round_id,
+                "reason": s.reason,
+                "motherlode": s.motherlode as f64 / ev::ONE_ORE as f64,
+                "created_at": s.created_at
+            })).collect::<Vec<_>>()
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Check every enabled schedule each `tick`, starting and stopping sessions
+async fn run_scheduler(state: Arc<AppState>, tick: std::time::Duration) {
+    let mut interval = tokio::time::interval(tick);
+    loop {
+        interval.tick().await;
+        let records = match state.db.list_schedules(None).await {
+            Ok(records) => records,
+            Err(e) => {
+                warn!("Scheduler: failed to load schedules: {}", e);
+                continue;
+            }
+        };
+        for record in records.into_iter().filter(|r| r.enabled) {
+            let id = record.id;
+            let result = match Schedule::from_record(record) {
+                Ok(schedule) => apply_schedule(&state, &schedule).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Schedule {}: {}", id, e);
+            }
+        }
+    }
+}
+
+/// Take a schedule's next action
+async fn apply_schedule(state: &AppState, schedule: &Schedule) -> Result<()> {
+    let now = chrono::Utc::now();
+    
+    // A run whose session was stopped some other way is over
+    let running = state.strategy_engine.session_id(&schedule.wallet);
+    let run = match schedule.session_id {
+        Some(session_id) if running == Some(session_id) => Some(Run {
+            started_at: schedule.last_started_at.unwrap_or(now),
+            rounds: state.strategy_engine.session_status(&schedule.wallet).await
+                .map(|status| (status.counters.rounds_played + status.counters.rounds_skipped) as i64)?,
+        }),
+        Some(_) => {
+            state.db.set_schedule_run(schedule.id, None, false).await?;
+            None
+        }
+        None => None,
+    };
     
+    match schedule.next_action(now, run.as_ref()) {
+        ScheduleAction::Wait => {}
+        ScheduleAction::Start => {
+            if state.strategy_engine.is_mining(&schedule.wallet) {
+                return Ok(());
+            }
+            let req: StartSessionRequest = serde_json::from_value(schedule.session.clone())?;
+            match launch_session(state, req).await {
+                Ok(started) => {
+                    state.db.set_schedule_run(schedule.id, Some(started.session_id), true).await?;
+                    info!("Schedule {} started session {} for {}", schedule.id, started.session_id, schedule.wallet);
+                }
+                Err(e) => {
+                    // Don't retry until the next window or cron minute
+                    state.db.set_schedule_run(schedule.id, None, true).await?;
+                    anyhow::bail!("failed to start session: {}", e);
+                }
+            }
+        }
+        ScheduleAction::Stop(reason) => {
+            halt_session(state, &schedule.wallet).await?;
+            state.db.set_schedule_run(schedule.id, None, false).await?;
+            info!("Schedule {} stopped {}: {}", schedule.id, schedule.wallet, reason);
+        }
+        ScheduleAction::Expire => {
+            if run.is_some() {
+                halt_session(state, &schedule.wallet).await?;
+            }
+            state.db.set_schedule_run(schedule.id, None, false).await?;
+            state.db.set_schedule_enabled(schedule.id, false).await?;
+            info!("Schedule {} for {} has ended", schedule.id, schedule.wallet);
+        }
+    }
     Ok(())
 }
 
-// =============================================================================
-// API Handlers
-// =============================================================================
-
-/// Health check endpoint
-async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "version": "1.0.0",
-        "network": "mainnet-beta"
-    }))
-}
-
-/// Start mining session request
+/// Create or replace schedule request
 #[derive(Debug, Deserialize)]
-pub struct StartSessionRequest {
-    pub wallet: String,
-    pub strategy: Strategy,
-    pub deploy_amount: f64,
-    pub max_tip: f64,
-    pub budget: f64,
-    #[serde(default = "default_num_blocks")]
-    pub num_blocks: u8,
+pub struct ScheduleRequest {
+    pub spec: ScheduleSpec,
+    /// `/api/session/start` body each run is started with
+    pub session: serde_json::Value,
+    #[serde(default)]
+    pub max_rounds: Option<i64>,
+    #[serde(default)]
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
 }
 
-fn default_num_blocks() -> u8 { 1 }
+fn default_enabled() -> bool { true }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum Strategy {
-    BestEv,
-    Conservative,
-    Aggressive,
+impl ScheduleRequest {
+    /// Check the request, returning the wallet it schedules
+    fn validate(&self) -> Result<String> {
+        let session: StartSessionRequest = serde_json::from_value(self.session.clone())
+            .map_err(|e| anyhow::anyhow!("invalid session request: {}", e))?;
+        let schedule = Schedule {
+            id: uuid::Uuid::nil(),
+            wallet: session.wallet.clone(),
+            spec: self.spec.clone(),
+            session: self.session.clone(),
+            max_rounds: self.max_rounds,
+            until: self.until,
+            enabled: self.enabled,
+            session_id: None,
+            last_started_at: None,
+            created_at: chrono::Utc::now(),
+        };
+        schedule.validate()?;
+        Ok(session.wallet)
+    }
 }
 
-/// Start a mining session
-async fn start_session(
+/// Query parameters for listing schedules
+#[derive(Debug, Deserialize)]
+pub struct SchedulesQuery {
+    #[serde(default)]
+    pub wallet: Option<String>,
+}
+
+/// Create a session schedule
+async fn create_schedule(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<StartSessionRequest>,
+    Json(req): Json<ScheduleRequest>,
 ) -> impl IntoResponse {
-    // Basic input validation (safety): prevent accidental catastrophic SOL amounts.
-    // These values come from user input (frontend) and are interpreted as SOL.
-    // Minimum 0.0001 SOL (100,000 lamports) per square
-    if !req.deploy_amount.is_finite() || req.deploy_amount < 0.0001 || req.deploy_amount > 10.0 {
-        return Json(serde_json::json!({
+    let wallet = match req.validate() {
+        Ok(wallet) => wallet,
+        Err(e) => return Json(serde_json::json!({
             "success": false,
-            "error": "deploy_amount must be >= 0.0001 and <= 10 (SOL)"
-        }));
-    }
+            "error": e.to_string()
+        }))
+    };
     
-    // Log the received values for debugging
-    tracing::info!("Session request: wallet={}, deploy_amount={} SOL ({} lamports), num_blocks={}",
-        req.wallet,
-        req.deploy_amount,
-        (req.deploy_amount * 1_000_000_000.0) as u64,
-        req.num_blocks
-    );
-    if !req.max_tip.is_finite() || req.max_tip < 0.0 || req.max_tip > 1.0 {
-        return Json(serde_json::json!({
-            "success": false,
-            "error": "max_tip must be >= 0 and <= 1 (SOL)"
-        }));
-    }
-    if !req.budget.is_finite() || req.budget <= 0.0 {
-        return Json(serde_json::json!({
+    let spec = serde_json::to_value(&req.spec).unwrap_or_default();
+    let saved = state.db
+        .create_schedule(&wallet, &spec, &req.session, req.max_rounds, req.until, req.enabled)
+        .await
+        .and_then(Schedule::from_record);
+    match saved {
+        Ok(schedule) => Json(serde_json::json!({
+            "success": true,
+            "schedule": schedule
+        })),
+        Err(e) => Json(serde_json::json!({
             "success": false,
-            "error": "budget must be > 0 (SOL)"
-        }));
+            "error": e.to_string()
+        }))
     }
-    let num_blocks = req.num_blocks.clamp(1, 25);
+}
 
-    // Verify wallet signature for authentication
-    // In production, verify the signature against a known message
-    
-    // Convert f64 to lamports (i64)
-    let max_tip_lamports = (req.max_tip * 1_000_000_000.0) as i64;
-    let deploy_lamports = (req.deploy_amount * 1_000_000_000.0) as i64;
-    let budget_lamports = (req.budget * 1_000_000_000.0) as i64;
-    
-    match state.db.create_session(
-        &req.wallet,
-        req.strategy.clone(),
-        max_tip_lamports,
-        deploy_lamports,
-        budget_lamports,
-    ).await {
-        Ok(session) => {
-            // Start the strategy engine for this wallet
-            let mut engine = state.strategy_engine.write().await;
-            engine.start_session(session.id, req.wallet.clone(), req.strategy, req.deploy_amount, req.max_tip, num_blocks).await;
-            
-            info!("Started session {} for wallet {}", session.id, req.wallet);
+/// List schedules, optionally for one wallet
+async fn list_schedules(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SchedulesQuery>,
+) -> impl IntoResponse {
+    match state.db.list_schedules(query.wallet.as_deref()).await {
+        Ok(records) => {
+            let schedules: Vec<Schedule> = records.into_iter()
+                .filter_map(|record| Schedule::from_record(record).ok())
+                .collect();
             Json(serde_json::json!({
                 "success": true,
-                "session_id": session.id
-            }))
-        }
-        Err(e) => {
-            Json(serde_json::json!({
-                "success": false,
-                "error": e.to_string()
+                "schedules": schedules
             }))
         }
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
     }
 }
 
-/// Stop mining session request
-#[derive(Debug, Deserialize)]
-pub struct StopSessionRequest {
-    pub wallet: String,
+/// Get a schedule
+async fn get_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    match state.db.get_schedule(id).await.and_then(|r| r.map(Schedule::from_record).transpose()) {
+        Ok(Some(schedule)) => Json(serde_json::json!({
+            "success": true,
+            "schedule": schedule
+        })),
+        Ok(None) => Json(serde_json::json!({
+            "success": false,
+            "error": format!("Schedule {} not found", id)
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
 }
 
-/// Stop a mining session
-async fn stop_session(
+/// Replace a schedule; a run in progress continues under the new limits
+async fn update_schedule(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<StopSessionRequest>,
+    Path(id): Path<uuid::Uuid>,
+    Json(req): Json<ScheduleRequest>,
 ) -> impl IntoResponse {
-    let mut engine = state.strategy_engine.write().await;
-    engine.stop_session(&req.wallet).await;
+    let existing = match state.db.get_schedule(id).await {
+        Ok(Some(existing)) => existing,
+        Ok(None) => return Json(serde_json::json!({
+            "success": false,
+            "error": format!("Schedule {} not found", id)
+        })),
+        Err(e) => return Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    };
+    match req.validate() {
+        Ok(wallet) if wallet != existing.wallet => return Json(serde_json::json!({
+            "success": false,
+            "error": "A schedule's wallet can't be changed"
+        })),
+        Ok(_) => {}
+        Err(e) => return Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
     
-    match state.db.end_session(&req.wallet).await {
-        Ok(_) => {
-            info!("Stopped session for wallet {}", req.wallet);
-            Json(serde_json::json!({
-                "success": true
-            }))
-        }
-        Err(e) => {
-            Json(serde_json::json!({
-                "success": false,
-                "error": e.to_string()
-            }))
+    let spec = serde_json::to_value(&req.spec).unwrap_or_default();
+    let saved = state.db
+        .update_schedule(id, &spec, &req.session, req.max_rounds, req.until, req.enabled)
+        .await
+        .and_then(|r| r.map(Schedule::from_record).transpose());
+    match saved {
+        Ok(schedule) => Json(serde_json::json!({
+            "success": true,
+            "schedule": schedule
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Delete a schedule, stopping its run in progress
+async fn delete_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<uuid::Uuid>,
+) -> impl IntoResponse {
+    let result = async {
+        let Some(schedule) = state.db.get_schedule(id).await? else {
+            anyhow::bail!("Schedule {} not found", id);
+        };
+        let running = state.strategy_engine.session_id(&schedule.wallet);
+        if schedule.session_id.is_some() && running == schedule.session_id {
+            halt_session(&state, &schedule.wallet).await?;
         }
+        state.db.delete_schedule(id).await
+    }.await;
+    match result {
+        Ok(_) => Json(serde_json::json!({
+            "success": true
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
     }
 }
 
-/// Query parameters for stats
-#[derive(Debug, Deserialize)]
-pub struct StatsQuery {
-    pub wallet: String,
+/// Portfolio settings and the most recently planned round
+async fn get_portfolio(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "config": state.portfolio.config(),
+        "last_round": state.portfolio.last_round()
+    }))
 }
 
-/// Get session statistics
-async fn get_stats(
+/// Risk limits and the wallet's current risk state
+async fn get_risk(
     State(state): State<Arc<AppState>>,
     Query(query): Query<StatsQuery>,
 ) -> impl IntoResponse {
-    // First get the active session for the wallet
-    match state.db.get_active_session(&query.wallet).await {
-        Ok(Some(session)) => {
-            match state.db.get_session_stats(session.id).await {
-                Ok(stats) => Json(serde_json::json!({
-                    "success": true,
-                    "stats": stats
-                })),
-                Err(e) => Json(serde_json::json!({
-                    "success": false,
-                    "error": e.to_string()
-                }))
-            }
-        }
-        Ok(None) => Json(serde_json::json!({
-            "success": false,
-            "error": "No active session found"
+    match state.risk.facts(&query.wallet).await {
+        Ok(facts) => Json(serde_json::json!({
+            "success": true,
+            "limits": state.risk.limits(),
+            "kill_switch": facts.kill_switch,
+            "kill_reason": facts.kill_reason,
+            "drawdown": facts.drawdown as f64 / 1_000_000_000.0,
+            "daily_loss": facts.daily_loss as f64 / 1_000_000_000.0,
+            "loss_streak": facts.loss_streak,
+            "cooldown_until": facts.cooldown_until
         })),
         Err(e) => Json(serde_json::json!({
             "success": false,
@@ -358,27 +1745,36 @@ async fn get_stats(
     }
 }
 
-/// Query parameters for transactions
+/// Kill switch request
 #[derive(Debug, Deserialize)]
-pub struct TransactionsQuery {
-    pub wallet: String,
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
+pub struct KillSwitchRequest {
+    pub reason: String,
 }
 
-/// Get transaction history
-async fn get_transactions(
+/// Stop every session from deploying until the kill switch is reset
+async fn engage_kill_switch(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<TransactionsQuery>,
+    Json(req): Json<KillSwitchRequest>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(50);
-    let offset = query.offset.unwrap_or(0);
-    
-    match state.db.get_transactions(&query.wallet, limit, offset).await {
-        Ok(transactions) => Json(serde_json::json!({
-            "success": true,
-            "transactions": transactions
-        })),
+    match state.risk.kill(&req.reason).await {
+        Ok(()) => {
+            warn!("Kill switch engaged via API: {}", req.reason);
+            Json(serde_json::json!({ "success": true }))
+        }
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
+/// Clear the kill switch; drawdown is measured from now on
+async fn reset_kill_switch(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.risk.reset().await {
+        Ok(()) => {
+            info!("Kill switch reset via API");
+            Json(serde_json::json!({ "success": true }))
+        }
         Err(e) => Json(serde_json::json!({
             "success": false,
             "error": e.to_string()
@@ -765,35 +2161,19 @@ async fn get_ai_suggestion(
     // Calculate EV for each square
     let deploy_lamports = (req.deploy_amount * 1_000_000_000.0) as u64;
     let tip_lamports = (req.tip_amount * 1_000_000_000.0) as u64;
-    let total_pot = round.total_deployed;
-    
-    let mut square_evs: Vec<(u8, f64)> = round.blocks.iter().map(|block| {
-        let block_deployed = block.total_deployed;
-        let other_squares_pot = total_pot.saturating_sub(block_deployed);
-        
-        // Win probability = my_stake / (block_total + my_stake)
-        let my_new_total = block_deployed + deploy_lamports;
-        let win_probability = if my_new_total > 0 {
-            deploy_lamports as f64 / my_new_total as f64
-        } else {
-            1.0 // Empty square, 100% win if we're first
-        };
-        
-        // Expected winnings = probability * pot from other squares
-        let expected_winnings = win_probability * other_squares_pot as f64;
-        
-        // Cost = deploy amount + tip
-        let cost = (deploy_lamports + tip_lamports) as f64;
-        
-        // EV = expected winnings - cost (in lamports)
-        let ev = expected_winnings - cost;
-        let ev_sol = ev / 1_000_000_000.0;
-        
-        (block.index, ev_sol)
+    let params = ev::EvParams { ore_price: state.prices.ore_price_lamports().await, ..Default::default() };
+    let deployed: Vec<u64> = round.blocks.iter().map(|b| b.total_deployed).collect();
+    
+    // EV in lamports of deploying on each square alone (see `ev` for the payoff model)
+    let mut square_evs: Vec<(u8, i64)> = round.blocks.iter().map(|block| {
+        let ev = params.single(&deployed, round.motherlode, block.index as usize, deploy_lamports, tip_lamports);
+        (block.index, ev.ev)
     }).collect();
     
     // Sort by EV descending
-    square_evs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    square_evs.sort_by(|a, b| b.1.cmp(&a.1));
+    // Squares losing more than a tenth of the deploy are avoided
+    let consider_floor = -((deploy_lamports / 10) as i64);
     
     // Select top N squares
     let num_to_select = (req.num_squares as usize).min(25);
@@ -801,17 +2181,17 @@ async fn get_ai_suggestion(
         let block = &round.blocks[*idx as usize];
         serde_json::json!({
             "square": idx,
-            "ev": ev,
+            "ev": *ev as f64 / 1_000_000_000.0,
             "deployed": block.total_deployed as f64 / 1_000_000_000.0,
             "miner_count": block.miner_count,
-            "recommendation": if *ev > 0.0 { "strong_buy" } else if *ev > -req.deploy_amount * 0.1 { "consider" } else { "avoid" }
+            "recommendation": if *ev > 0 { "strong_buy" } else if *ev > consider_floor { "consider" } else { "avoid" }
         })
     }).collect();
     
     // Calculate aggregate stats
-    let positive_ev_count = square_evs.iter().filter(|(_, ev)| *ev > 0.0).count();
-    let best_ev = square_evs.first().map(|(_, ev)| *ev).unwrap_or(0.0);
-    let should_play = best_ev > 0.0 || positive_ev_count >= 3;
+    let positive_ev_count = square_evs.iter().filter(|(_, ev)| *ev > 0).count();
+    let best_ev = square_evs.first().map(|(_, ev)| *ev).unwrap_or(0);
+    let should_play = best_ev > 0 || positive_ev_count >= 3;
     
     Json(serde_json::json!({
         "success": true,
@@ -820,7 +2200,7 @@ async fn get_ai_suggestion(
             "total_pot": round.total_deployed as f64 / 1_000_000_000.0,
             "total_miners": round.total_miners,
             "positive_ev_squares": positive_ev_count,
-            "best_ev": best_ev,
+            "best_ev": best_ev as f64 / 1_000_000_000.0,
             "should_play": should_play
         },
         "suggested_squares": selected,
@@ -836,6 +2216,37 @@ async fn get_ai_suggestion(
     }))
 }
 
+/// Per-region Jito block engine acceptance and latency
+async fn get_jito_regions(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let regions: Vec<serde_json::Value> = state.jito_client.region_stats()
+        .into_iter()
+        .map(|(name, stats)| serde_json::json!({
+            "region": name,
+            "acceptance_rate": stats.acceptance_rate(),
+            "stats": stats
+        }))
+        .collect();
+    
+    Json(serde_json::json!({
+        "success": true,
+        "regions": regions
+    }))
+}
+
+/// Per-path submission acceptance, latency and landings, plus rebroadcast counts
+async fn get_submission_paths(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "enabled": state.tx_sender.policy().paths,
+        "paths": state.tx_sender.path_stats(),
+        "rebroadcast": state.rebroadcaster.stats()
+    }))
+}
+
 /// WebSocket upgrade handler
 async fn ws_handler(
     State(state): State<Arc<AppState>>,
@@ -935,6 +2346,34 @@ async fn list_wallets(
     }))
 }
 
+/// Replace a managed wallet's tags
+#[derive(Debug, Deserialize)]
+pub struct WalletTagsRequest {
+    pub wallet_address: String,
+    pub tags: Vec<String>,
+}
+
+async fn set_wallet_tags(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WalletTagsRequest>,
+) -> impl IntoResponse {
+    match state.db.set_wallet_tags(&req.wallet_address, &req.tags).await {
+        Ok(true) => Json(serde_json::json!({
+            "success": true,
+            "wallet_address": req.wallet_address,
+            "tags": req.tags
+        })),
+        Ok(false) => Json(serde_json::json!({
+            "success": false,
+            "error": "Wallet not found"
+        })),
+        Err(e) => Json(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+    }
+}
+
 /// Export a wallet's private key
 #[derive(Debug, Deserialize)]
 pub struct ExportWalletRequest {
@@ -0,0 +1,265 @@
+//! Local Block-Selection Model
+//!
+//! Logistic scoring of squares from the grid snapshot alone, so sessions get
+//! a learned selector without an LLM provider. The model predicts whether a
+//! square finishes the round with less stake than the average square (the
+//! pro-rata payoff is best there), trained offline by the `train` subcommand
+//! from AI decision snapshots joined with final round stakes.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::ai::{AiSelection, GridState};
+use crate::db::Database;
+
+/// Default location of the trained weights
+pub const DEFAULT_MODEL_PATH: &str = "block_model.json";
+
+/// Model file format version
+const MODEL_VERSION: u32 = 1;
+
+/// Per-square features, followed by a one-hot square index
+const FEATURE_NAMES: [&str; 5] = [
+    "stake_share",
+    "log_stake_sol",
+    "log_miners",
+    "stake_vs_mean",
+    "late_inflow_share",
+];
+
+const NUM_FEATURES: usize = FEATURE_NAMES.len() + 25;
+
+/// Feature vector for one square of a grid snapshot
+pub fn features(grid: &GridState, index: usize) -> Vec<f64> {
+    let deployed = |i: usize| grid.deployed.get(i).copied().unwrap_or(0) as f64;
+    let total: f64 = grid.deployed.iter().map(|&d| d as f64).sum();
+    let mean = total / 25.0;
+    let stake = deployed(index);
+    let miners = grid.miner_counts.get(index).copied().unwrap_or(0) as f64;
+    let inflow = grid.recent_inflow.get(index).copied().unwrap_or(0) as f64;
+
+    let mut x = Vec::with_capacity(NUM_FEATURES);
+    x.push(if total > 0.0 { stake / total } else { 0.0 });
+    x.push((stake / 1_000_000_000.0).ln_1p());
+    x.push(miners.ln_1p());
+    x.push(if mean > 0.0 { (stake / mean - 1.0).clamp(-1.0, 10.0) } else { 0.0 });
+    x.push(if total > 0.0 { inflow / total } else { 0.0 });
+    x.extend((0..25).map(|i| if i == index { 1.0 } else { 0.0 }));
+    x
+}
+
+/// Label: 1 if the square ended the round below the average square's stake
+fn label(final_deployed: &[u64], index: usize) -> f64 {
+    let mean = final_deployed.iter().map(|&d| d as f64).sum::<f64>() / 25.0;
+    let stake = final_deployed.get(index).copied().unwrap_or(0) as f64;
+    if stake < mean { 1.0 } else { 0.0 }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// A snapshot and how its round ended
+#[derive(Debug, Clone)]
+pub struct TrainingExample {
+    pub grid: GridState,
+    pub final_deployed: Vec<u64>,
+}
+
+/// Gradient-descent settings
+#[derive(Debug, Clone)]
+pub struct TrainConfig {
+    pub epochs: usize,
+    pub learning_rate: f64,
+    /// L2 penalty on weights (not the bias)
+    pub l2: f64,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 500,
+            learning_rate: 0.5,
+            l2: 1e-3,
+        }
+    }
+}
+
+/// Trained logistic model over per-square features
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockModel {
+    pub version: u32,
+    pub features: Vec<String>,
+    pub weights: Vec<f64>,
+    pub bias: f64,
+    /// Squares (rows x 25) the model was fit on
+    pub trained_examples: usize,
+    pub trained_at: DateTime<Utc>,
+}
+
+impl BlockModel {
+    /// Load weights saved by `train`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read model file {}", path.display()))?;
+        let model: Self = serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse model file {}", path.display()))?;
+
+        if model.version != MODEL_VERSION {
+            anyhow::bail!("Model file version {} is not supported (expected {})", model.version, MODEL_VERSION);
+        }
+        if model.weights.len() != NUM_FEATURES {
+            anyhow::bail!("Model has {} weights, expected {}", model.weights.len(), NUM_FEATURES);
+        }
+        Ok(model)
+    }
+
+    /// Save weights as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write model file {}", path.display()))
+    }
+
+    /// Probability that a square ends the round under-subscribed
+    pub fn score(&self, grid: &GridState, index: usize) -> f64 {
+        let z: f64 = features(grid, index).iter()
+            .zip(&self.weights)
+            .map(|(x, w)| x * w)
+            .sum();
+        sigmoid(z + self.bias)
+    }
+
+    /// Top `num_blocks` squares by score, weighted by score
+    pub fn select(&self, grid: &GridState, num_blocks: usize) -> AiSelection {
+        let mut scored: Vec<(u8, f64)> = (0..25).map(|i| (i as u8, self.score(grid, i))).collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(num_blocks.clamp(1, 25));
+
+        let total: f64 = scored.iter().map(|(_, p)| p).sum();
+        let confidence = total / scored.len() as f64;
+        AiSelection {
+            blocks: scored.iter().map(|(i, _)| *i).collect(),
+            weights: scored.iter()
+                .map(|(_, p)| if total > 0.0 { p / total } else { 1.0 / scored.len() as f64 })
+                .collect(),
+            confidence,
+            skip: false,
+            reasoning: format!(
+                "Local model: top {} square(s), p(under-subscribed) {}",
+                scored.len(),
+                scored.iter().map(|(i, p)| format!("{}={:.2}", i, p)).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+}
+
+/// Fit a logistic model with full-batch gradient descent
+pub fn train(examples: &[TrainingExample], config: &TrainConfig) -> Result<BlockModel> {
+    let rows: Vec<(Vec<f64>, f64)> = examples.iter()
+        .filter(|e| e.final_deployed.len() == 25)
+        .flat_map(|e| (0..25).map(move |i| (features(&e.grid, i), label(&e.final_deployed, i))))
+        .collect();
+    if rows.is_empty() {
+        anyhow::bail!("No training examples - run sessions with AI selection until some rounds resolve");
+    }
+
+    let n = rows.len() as f64;
+    let mut weights = vec![0.0; NUM_FEATURES];
+    let mut bias = 0.0;
+    for _ in 0..config.epochs {
+        let mut grad_w = vec![0.0; NUM_FEATURES];
+        let mut grad_b = 0.0;
+        for (x, y) in &rows {
+            let z: f64 = x.iter().zip(&weights).map(|(x, w)| x * w).sum::<f64>() + bias;
+            let err = sigmoid(z) - y;
+            for (g, xi) in grad_w.iter_mut().zip(x) {
+                *g += err * xi;
+            }
+            grad_b += err;
+        }
+        for (w, g) in weights.iter_mut().zip(&grad_w) {
+            *w -= config.learning_rate * (g / n + config.l2 * *w);
+        }
+        bias -= config.learning_rate * grad_b / n;
+    }
+
+    Ok(BlockModel {
+        version: MODEL_VERSION,
+        features: FEATURE_NAMES.iter()
+            .map(|s| s.to_string())
+            .chain((0..25).map(|i| format!("square_{}", i)))
+            .collect(),
+        weights,
+        bias,
+        trained_examples: rows.len(),
+        trained_at: Utc::now(),
+    })
+}
+
+/// Train from recorded AI decision snapshots of resolved rounds
+pub async fn train_from_db(db: &Database, config: &TrainConfig) -> Result<BlockModel> {
+    let rows = db.get_training_rows().await?;
+    let examples: Vec<TrainingExample> = rows.into_iter()
+        .filter_map(|row| match serde_json::from_value::<GridState>(row.grid) {
+            Ok(grid) => Some(TrainingExample {
+                grid,
+                final_deployed: row.final_deployed.iter().map(|&d| d.max(0) as u64).collect(),
+            }),
+            Err(e) => {
+                warn!("Skipping unreadable grid snapshot: {}", e);
+                None
+            }
+        })
+        .collect();
+    info!("Training local block model on {} snapshot(s)", examples.len());
+
+    train(examples.as_slice(), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(deployed: Vec<u64>) -> GridState {
+        GridState {
+            total_pot: deployed.iter().sum(),
+            deployed,
+            miner_counts: vec![1; 25],
+            round_id: 1,
+            slots_remaining: 10,
+            deploy_amount: 1_000_000,
+            tip_cost: 0,
+            motherlode: 0,
+            recent_inflow: vec![],
+        }
+    }
+
+    #[test]
+    fn test_learns_low_stake_squares_stay_light() {
+        // Squares that are light at snapshot time stay light by the end
+        let examples: Vec<TrainingExample> = (0..20)
+            .map(|r| {
+                let deployed: Vec<u64> = (0..25).map(|i| ((i + r) % 25) as u64 * 100_000_000).collect();
+                TrainingExample { final_deployed: deployed.iter().map(|d| d * 2).collect(), grid: grid(deployed) }
+            })
+            .collect();
+        let model = train(&examples, &TrainConfig::default()).unwrap();
+
+        let snapshot = grid((0..25).map(|i| (24 - i) as u64 * 100_000_000).collect());
+        let selection = model.select(&snapshot, 3);
+        assert_eq!(selection.blocks.len(), 3);
+        assert!(selection.blocks.iter().all(|&b| b >= 20), "picked {:?}", selection.blocks);
+        assert!((selection.weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_train_requires_examples() {
+        assert!(train(&[], &TrainConfig::default()).is_err());
+    }
+}
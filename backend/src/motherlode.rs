@@ -0,0 +1,138 @@
+//! Motherlode Hunting
+//!
+//! A session mode that sits out ordinary rounds and plays only when the
+//! motherlode is worth it: when it reaches a size threshold, or when its
+//! expected value per deployed square (at the ORE price) crosses a threshold.
+//! The mode sets its own stake and square count, on the lowest-stake squares.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::ev::{EvParams, ONE_ORE};
+
+/// Motherlode mode settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MotherlodeMode {
+    /// Play when the motherlode is at least this many ORE
+    #[serde(default)]
+    pub min_motherlode: Option<f64>,
+    /// Play when the motherlode's expected value per square is at least this (SOL)
+    #[serde(default)]
+    pub min_ev_per_square: Option<f64>,
+    /// SOL per square when playing
+    pub per_square: f64,
+    /// Squares deployed when playing
+    pub squares: u8,
+}
+
+/// Whether a round is worth playing for the motherlode
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HuntVerdict {
+    pub play: bool,
+    /// Motherlode at decision time (grains)
+    pub motherlode: u64,
+    /// Expected motherlode value per square (lamports)
+    pub ev_per_square: u64,
+    pub reason: String,
+}
+
+impl MotherlodeMode {
+    /// Check thresholds and stake
+    pub fn validate(&self) -> Result<()> {
+        if self.min_motherlode.is_none() && self.min_ev_per_square.is_none() {
+            anyhow::bail!("motherlode mode needs min_motherlode or min_ev_per_square");
+        }
+        if self.min_motherlode.is_some_and(|m| !m.is_finite() || m < 0.0)
+            || self.min_ev_per_square.is_some_and(|m| !m.is_finite() || m < 0.0)
+        {
+            anyhow::bail!("motherlode thresholds must be >= 0");
+        }
+        if !self.per_square.is_finite() || self.per_square < 0.0001 || self.per_square > 10.0 {
+            anyhow::bail!("motherlode per_square must be >= 0.0001 and <= 10 (SOL)");
+        }
+        if !(1..=25).contains(&self.squares) {
+            anyhow::bail!("motherlode squares must be 1-25");
+        }
+        Ok(())
+    }
+
+    /// Lamports per square when playing
+    pub fn per_square_lamports(&self) -> u64 {
+        (self.per_square * 1_000_000_000.0) as u64
+    }
+
+    /// Judge a round from its stakes and motherlode, for a deploy on `squares`
+    pub fn evaluate(&self, params: &EvParams, deployed: &[u64], motherlode: u64, squares: &[u8]) -> HuntVerdict {
+        // Only the motherlode's share of the ORE, not the regular emission
+        let lode_only = EvParams { ore_per_round: 0, ..*params };
+        let ev = lode_only.uniform(deployed, motherlode, squares, self.per_square_lamports(), 0);
+        let ev_per_square = lode_only.ore_value(ev.expected_ore) / squares.len().max(1) as u64;
+
+        let lode_ore = motherlode as f64 / ONE_ORE as f64;
+        let ev_sol = ev_per_square as f64 / 1_000_000_000.0;
+        let by_size = self.min_motherlode.map(|min| lode_ore >= min);
+        let by_ev = self.min_ev_per_square.map(|min| ev_sol >= min);
+
+        let reason = match (by_size, by_ev) {
+            (Some(true), _) => format!("Motherlode {:.2} ORE >= {:.2} ORE", lode_ore, self.min_motherlode.unwrap_or(0.0)),
+            (_, Some(true)) => format!(
+                "Motherlode EV {:.6} SOL/square >= {:.6} SOL", ev_sol, self.min_ev_per_square.unwrap_or(0.0)
+            ),
+            _ => {
+                let mut parts = Vec::new();
+                if let Some(min) = self.min_motherlode {
+                    parts.push(format!("motherlode {:.2} ORE < {:.2} ORE", lode_ore, min));
+                }
+                if let Some(min) = self.min_ev_per_square {
+                    parts.push(format!("EV {:.6} SOL/square < {:.6} SOL", ev_sol, min));
+                }
+                format!("Motherlode hunt: {}", parts.join(", "))
+            }
+        };
+
+        HuntVerdict {
+            play: by_size == Some(true) || by_ev == Some(true),
+            motherlode,
+            ev_per_square,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn mode(min_motherlode: Option<f64>, min_ev_per_square: Option<f64>) -> MotherlodeMode {
+        MotherlodeMode { min_motherlode, min_ev_per_square, per_square: 0.01, squares: 2 }
+    }
+
+    #[test]
+    fn test_size_threshold() {
+        let params = EvParams::default();
+        let deployed = [SOL; 25];
+        let hunt = mode(Some(100.0), None);
+        assert!(!hunt.evaluate(&params, &deployed, 50 * ONE_ORE, &[0, 1]).play);
+
+        let verdict = hunt.evaluate(&params, &deployed, 150 * ONE_ORE, &[0, 1]);
+        assert!(verdict.play);
+        assert_eq!(verdict.motherlode, 150 * ONE_ORE);
+        assert!(mode(None, None).validate().is_err());
+    }
+
+    #[test]
+    fn test_ev_threshold_scales_with_motherlode_and_price() {
+        let deployed = [SOL / 100; 25];
+        let hunt = mode(None, Some(0.0001));
+        // Worthless ORE never crosses an EV threshold
+        assert!(!hunt.evaluate(&EvParams::default(), &deployed, 1_000 * ONE_ORE, &[0, 1]).play);
+
+        let priced = EvParams { ore_price: SOL, ..EvParams::default() };
+        let small = hunt.evaluate(&priced, &deployed, ONE_ORE, &[0, 1]);
+        let large = hunt.evaluate(&priced, &deployed, 1_000 * ONE_ORE, &[0, 1]);
+        assert!(!small.play && large.play, "{:?} / {:?}", small, large);
+        assert!(large.ev_per_square > small.ev_per_square);
+    }
+}
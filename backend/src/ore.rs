@@ -8,17 +8,62 @@ use std::sync::Arc;
 use anyhow::{Result, Context};
 use ore_api::state::{board_pda, round_pda, miner_pda, treasury_pda};
 use solana_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use serde::Serialize;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    instruction::InstructionError,
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 use tracing::{debug, info, warn};
 
 /// ORE v3 Program ID on Mainnet
 pub const ORE_PROGRAM_ID: Pubkey = ore_api::ID;
 
+/// ORE program error decoded from `InstructionError::Custom`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OreProgramError {
+    AmountTooSmall,
+    NotAuthorized,
+    /// Custom code not known to ore-api
+    Unknown(u32),
+}
+
+impl OreProgramError {
+    /// Map a custom program error code to the ore-api `OreError` it represents
+    pub fn from_code(code: u32) -> Self {
+        use ore_api::error::OreError;
+        match code {
+            c if c == OreError::AmountTooSmall as u32 => Self::AmountTooSmall,
+            c if c == OreError::NotAuthorized as u32 => Self::NotAuthorized,
+            c => Self::Unknown(c),
+        }
+    }
+    
+    /// Decode the ORE error from a transaction error, if the failing
+    /// instruction belongs to the ORE program
+    pub fn from_transaction_error(tx: &Transaction, err: &TransactionError) -> Option<Self> {
+        let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+            return None;
+        };
+        let ix = tx.message.instructions.get(*index as usize)?;
+        let program_id = tx.message.account_keys.get(ix.program_id_index as usize)?;
+        (*program_id == ORE_PROGRAM_ID).then(|| Self::from_code(*code))
+    }
+}
+
+impl std::fmt::Display for OreProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AmountTooSmall => write!(f, "amount too small"),
+            Self::NotAuthorized => write!(f, "not authorized"),
+            Self::Unknown(code) => write!(f, "unknown ORE error {}", code),
+        }
+    }
+}
+
 /// Block data from ORE grid (5x5 = 25 squares)
 #[derive(Debug, Clone)]
 pub struct BlockData {
@@ -40,10 +85,29 @@ pub struct RoundState {
     pub total_miners: u64,
     pub motherlode: u64,
     pub top_miner: Pubkey,
+    /// ORE minted for the round (grains)
+    pub top_miner_reward: u64,
     pub blocks: [BlockData; 25],
     pub slot_hash: [u8; 32],
 }
 
+impl RoundState {
+    /// Winning square once the round's slot hash is set
+    ///
+    /// Mirrors ore-api: the RNG is the XOR of the slot hash's four u64 chunks,
+    /// and the winning square is that value mod 25.
+    pub fn winning_square(&self) -> Option<u8> {
+        if self.slot_hash == [0; 32] || self.slot_hash == [u8::MAX; 32] {
+            return None;
+        }
+        let rng = self.slot_hash
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .fold(0, |acc, x| acc ^ x);
+        Some((rng % 25) as u8)
+    }
+}
+
 /// Board state (current round info)
 #[derive(Debug, Clone)]
 pub struct BoardState {
@@ -70,6 +134,14 @@ pub struct MinerData {
     pub lifetime_deployed: u64,
 }
 
+impl MinerData {
+    /// Whether the miner must checkpoint its last round before deploying in
+    /// `board_round`: deploy requires `checkpoint_id == round_id`
+    pub fn needs_checkpoint(&self, board_round: u64) -> bool {
+        self.checkpoint_id != self.round_id || (self.round_id > 0 && self.round_id < board_round)
+    }
+}
+
 /// ORE v3 client for interacting with the program
 #[derive(Clone)]
 pub struct OreClient {
@@ -182,6 +254,7 @@ impl OreClient {
         offset += 32;
         
         // top_miner_reward: u64
+        let top_miner_reward = u64::from_le_bytes(round_data[offset..offset+8].try_into()?);
         offset += 8;
         
         // total_deployed: u64
@@ -219,6 +292,7 @@ impl OreClient {
             total_miners,
             motherlode,
             top_miner,
+            top_miner_reward,
             blocks,
             slot_hash,
         })
@@ -620,6 +694,31 @@ mod tests {
         assert_eq!(ORE_PROGRAM_ID.to_string(), "oreV3EG1i9BEgiAJ8b177Z2S2rMarzak4NMv1kULvWv");
     }
     
+    #[test]
+    fn test_winning_square() {
+        let mut round = RoundState {
+            round_id: 1,
+            start_slot: 0,
+            end_slot: 150,
+            expires_at: 0,
+            total_deployed: 0,
+            total_vaulted: 0,
+            total_winnings: 0,
+            total_miners: 0,
+            motherlode: 0,
+            top_miner: Pubkey::default(),
+            top_miner_reward: 0,
+            blocks: std::array::from_fn(|i| BlockData { index: i as u8, total_deployed: 0, miner_count: 0 }),
+            slot_hash: [0; 32],
+        };
+        assert_eq!(round.winning_square(), None);
+        
+        // Chunks 1, 2, 0, 0 -> rng 3
+        round.slot_hash[0] = 1;
+        round.slot_hash[8] = 2;
+        assert_eq!(round.winning_square(), Some(3));
+    }
+    
     #[test]
     fn test_pdas() {
         let (board, _) = board_pda();
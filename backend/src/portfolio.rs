@@ -0,0 +1,398 @@
+//! Portfolio Coordination
+//!
+//! Sessions mining with different wallets at once would otherwise all pile
+//! onto the same lowest-stake squares and bid against each other. Each
+//! session hands its planned allocation to the coordinator at the submission
+//! window; once every session has reported (or a short wait runs out) the
+//! round is planned as a whole:
+//!
+//! - `deconflict` keeps each wallet's squares, moving any another of our
+//!   wallets already holds to the thinnest free square
+//! - `spread` ignores the wallets' choices and spreads their combined stake
+//!   over the thinnest squares as one portfolio
+//! - an optional cap on the SOL deployed per round scales every wallet down
+//!
+//! Sessions that report after the round was planned are fitted around what
+//! was already handed out.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use tracing::info;
+
+use crate::allocation::Allocation;
+
+/// How the wallets' squares are coordinated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortfolioMode {
+    /// Wallets keep their own squares (the exposure cap still applies)
+    Off,
+    /// Move squares another wallet already holds
+    #[default]
+    Deconflict,
+    /// Spread all wallets over the thinnest squares as one portfolio
+    Spread,
+}
+
+impl FromStr for PortfolioMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "none" | "0" => Ok(Self::Off),
+            "deconflict" => Ok(Self::Deconflict),
+            "spread" | "portfolio" => Ok(Self::Spread),
+            other => anyhow::bail!("Unknown portfolio mode: {}", other),
+        }
+    }
+}
+
+/// Portfolio settings
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioConfig {
+    pub mode: PortfolioMode,
+    /// Most lamports deployed across all wallets in one round
+    pub max_round_exposure: Option<u64>,
+    /// How long a session waits for the others to report
+    #[serde(skip)]
+    pub wait: Duration,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            mode: PortfolioMode::Deconflict,
+            max_round_exposure: None,
+            wait: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Thinnest square not excluded by `skip`
+fn thinnest(stake: &[u64; 25], skip: impl Fn(usize) -> bool) -> Option<usize> {
+    (0..25).filter(|&i| !skip(i)).min_by_key(|&i| stake[i])
+}
+
+/// Add an allocation's amounts to the stake and mark its squares held
+fn hold(allocation: &Allocation, stake: &mut [u64; 25], taken: &mut [bool; 25]) {
+    for (i, &amount) in allocation.amounts().iter().enumerate().filter(|(_, &a)| a > 0) {
+        stake[i] += amount;
+        taken[i] = true;
+    }
+}
+
+impl PortfolioConfig {
+    /// Whether coordination changes anything
+    pub fn is_active(&self) -> bool {
+        self.mode != PortfolioMode::Off || self.max_round_exposure.is_some()
+    }
+
+    /// Plan `proposals`, in order, around the allocations already `committed`
+    pub fn plan(&self, deployed: &[u64], committed: &[Allocation], proposals: &[Allocation]) -> Vec<Allocation> {
+        // Stake on each square, counting what our own wallets put there
+        let mut stake = [0u64; 25];
+        for (i, s) in stake.iter_mut().enumerate() {
+            *s = deployed.get(i).copied().unwrap_or(0);
+        }
+        let mut taken = [false; 25];
+        committed.iter().for_each(|a| hold(a, &mut stake, &mut taken));
+
+        let mut planned = Vec::with_capacity(proposals.len());
+        for proposal in proposals {
+            let allocation = match self.mode {
+                PortfolioMode::Off => *proposal,
+                PortfolioMode::Deconflict => Self::deconflict(proposal, &stake, &taken),
+                PortfolioMode::Spread => Self::spread(proposal, &stake, &taken),
+            };
+            hold(&allocation, &mut stake, &mut taken);
+            planned.push(allocation);
+        }
+
+        if let Some(cap) = self.max_round_exposure {
+            let used: u64 = committed.iter().map(Allocation::total).sum();
+            let wanted: u64 = planned.iter().map(Allocation::total).sum();
+            let room = cap.saturating_sub(used);
+            if wanted > room {
+                let factor = room as f64 / wanted as f64;
+                planned = planned.iter().map(|a| a.scaled(factor)).collect();
+            }
+        }
+        planned
+    }
+
+    fn deconflict(proposal: &Allocation, stake: &[u64; 25], taken: &[bool; 25]) -> Allocation {
+        let own = proposal.squares();
+        let mut mine = [false; 25];
+        own.iter().for_each(|&s| mine[s as usize] = true);
+
+        let mut squares = Vec::with_capacity(own.len());
+        let mut amounts = Vec::with_capacity(own.len());
+        for &square in &own {
+            let target = if taken[square as usize] {
+                // Nowhere free to go: stay and share the square
+                thinnest(stake, |i| taken[i] || mine[i]).unwrap_or(square as usize)
+            } else {
+                square as usize
+            };
+            mine[target] = true;
+            squares.push(target as u8);
+            amounts.push(proposal.amounts()[square as usize]);
+        }
+        Allocation::from_squares(&squares, &amounts)
+    }
+
+    fn spread(proposal: &Allocation, stake: &[u64; 25], taken: &[bool; 25]) -> Allocation {
+        let mut amounts: Vec<u64> = proposal.amounts().iter().copied().filter(|&a| a > 0).collect();
+        amounts.sort_unstable_by(|a, b| b.cmp(a));
+
+        // Largest amounts onto the thinnest squares, shared only once every square is held
+        let mut stake = *stake;
+        let mut mine = [false; 25];
+        let mut squares = Vec::with_capacity(amounts.len());
+        for &amount in &amounts {
+            let Some(target) = thinnest(&stake, |i| taken[i] || mine[i]).or_else(|| thinnest(&stake, |i| mine[i])) else {
+                break;
+            };
+            mine[target] = true;
+            stake[target] += amount;
+            squares.push(target as u8);
+        }
+        Allocation::from_squares(&squares, &amounts)
+    }
+}
+
+/// One wallet's part of a planned round
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletPlan {
+    pub wallet: String,
+    pub squares: Vec<u8>,
+    /// Lamports across the wallet's squares
+    pub amount: u64,
+    /// Whether the coordinator changed the wallet's own plan
+    pub adjusted: bool,
+}
+
+/// The most recently planned round
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioRound {
+    pub round_id: u64,
+    /// Lamports across all wallets
+    pub exposure: u64,
+    pub wallets: Vec<WalletPlan>,
+}
+
+/// Proposals collected for the current round
+#[derive(Debug, Default)]
+struct Book {
+    members: HashSet<String>,
+    round_id: u64,
+    proposals: Vec<(String, Allocation)>,
+    /// (wallet, proposed, planned) once the round is planned
+    plan: Option<Vec<(String, Allocation, Allocation)>>,
+}
+
+impl Book {
+    fn settle(&mut self, config: &PortfolioConfig, deployed: &[u64]) {
+        let proposals: Vec<Allocation> = self.proposals.iter().map(|(_, a)| *a).collect();
+        let planned = config.plan(deployed, &[], &proposals);
+        let plan: Vec<_> = self.proposals.drain(..)
+            .zip(planned)
+            .map(|((wallet, proposed), planned)| (wallet, proposed, planned))
+            .collect();
+        info!(
+            "Portfolio round {}: {} wallet(s), {} lamports",
+            self.round_id,
+            plan.iter().filter(|(_, _, p)| p.total() > 0).count(),
+            plan.iter().map(|(_, _, p)| p.total()).sum::<u64>()
+        );
+        self.plan = Some(plan);
+    }
+
+    /// A late wallet's plan, fitted around what was handed out
+    fn fit_late(&mut self, config: &PortfolioConfig, wallet: &str, deployed: &[u64], proposal: Allocation) -> Allocation {
+        let plan = self.plan.get_or_insert_with(Vec::new);
+        plan.retain(|(w, _, _)| w != wallet);
+        let committed: Vec<Allocation> = plan.iter().map(|(_, _, p)| *p).collect();
+        let planned = config.plan(deployed, &committed, &[proposal])[0];
+        plan.push((wallet.to_string(), proposal, planned));
+        planned
+    }
+}
+
+/// Coordinates the current round across every running session
+#[derive(Clone)]
+pub struct Portfolio {
+    config: Arc<PortfolioConfig>,
+    book: Arc<Mutex<Book>>,
+    notify: Arc<Notify>,
+}
+
+impl Portfolio {
+    pub fn new(config: PortfolioConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            book: Arc::new(Mutex::new(Book::default())),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PortfolioConfig {
+        &self.config
+    }
+
+    /// Count a session's wallet in every round until it leaves
+    pub fn join(&self, wallet: &str) {
+        self.book.lock().members.insert(wallet.to_string());
+    }
+
+    pub fn leave(&self, wallet: &str) {
+        self.book.lock().members.remove(wallet);
+    }
+
+    /// The most recently planned round
+    pub fn last_round(&self) -> Option<PortfolioRound> {
+        let book = self.book.lock();
+        let plan = book.plan.as_ref()?;
+        let wallets: Vec<WalletPlan> = plan.iter()
+            .filter(|(_, proposed, planned)| proposed.total() > 0 || planned.total() > 0)
+            .map(|(wallet, proposed, planned)| WalletPlan {
+                wallet: wallet.clone(),
+                squares: planned.squares(),
+                amount: planned.total(),
+                adjusted: proposed != planned,
+            })
+            .collect();
+        Some(PortfolioRound {
+            round_id: book.round_id,
+            exposure: wallets.iter().map(|w| w.amount).sum(),
+            wallets,
+        })
+    }
+
+    /// Hand in a wallet's allocation for the round (empty if it sits the round
+    /// out) and get back the one to deploy. Waits until every session has
+    /// reported or the wait runs out.
+    pub async fn coordinate(&self, round_id: u64, wallet: &str, deployed: &[u64], proposal: Allocation) -> Allocation {
+        // Created before reporting, so a plan settled in between still wakes us
+        let planned = self.notify.notified();
+        {
+            let mut book = self.book.lock();
+            // A session still on an earlier round leaves the current one's book alone
+            if round_id < book.round_id {
+                return proposal;
+            }
+            if round_id > book.round_id {
+                book.round_id = round_id;
+                book.proposals.clear();
+                book.plan = None;
+            }
+            if book.plan.is_some() {
+                return book.fit_late(&self.config, wallet, deployed, proposal);
+            }
+            book.proposals.retain(|(w, _)| w != wallet);
+            book.proposals.push((wallet.to_string(), proposal));
+            if book.proposals.len() >= book.members.len() {
+                book.settle(&self.config, deployed);
+                self.notify.notify_waiters();
+                return Self::planned_for(&book, wallet).unwrap_or(proposal);
+            }
+        }
+
+        let _ = tokio::time::timeout(self.config.wait, planned).await;
+
+        let mut book = self.book.lock();
+        if book.round_id != round_id {
+            return proposal;
+        }
+        if book.plan.is_none() {
+            book.settle(&self.config, deployed);
+            self.notify.notify_waiters();
+        }
+        match Self::planned_for(&book, wallet) {
+            Some(planned) => planned,
+            None => book.fit_late(&self.config, wallet, deployed, proposal),
+        }
+    }
+
+    fn planned_for(book: &Book, wallet: &str) -> Option<Allocation> {
+        book.plan.as_ref()?.iter().find(|(w, _, _)| w == wallet).map(|(_, _, p)| *p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn config(mode: PortfolioMode, max_round_exposure: Option<u64>) -> PortfolioConfig {
+        PortfolioConfig { mode, max_round_exposure, ..Default::default() }
+    }
+
+    fn deployed() -> Vec<u64> {
+        // Square i holds i+1 SOL, so 0 is the thinnest
+        (1..=25).map(|i| i * SOL).collect()
+    }
+
+    #[test]
+    fn test_deconflict_moves_only_shared_squares() {
+        let a = Allocation::uniform(&[0, 1], SOL / 100);
+        let b = Allocation::from_squares(&[1, 7], &[SOL / 50, SOL / 100]);
+        let plan = config(PortfolioMode::Deconflict, None).plan(&deployed(), &[], &[a, b]);
+
+        assert_eq!(plan[0], a);
+        assert_eq!(plan[1].squares(), vec![2, 7]);
+        assert_eq!(plan[1].amounts()[2], SOL / 50, "the amount moves with the square");
+        // A late wallet is fitted around the committed ones
+        let late = config(PortfolioMode::Deconflict, None).plan(&deployed(), &plan, &[a]);
+        assert_eq!(late[0].squares(), vec![3, 4]);
+        // Off leaves the wallets alone
+        assert_eq!(config(PortfolioMode::Off, None).plan(&deployed(), &[], &[a, a]), vec![a, a]);
+    }
+
+    #[test]
+    fn test_spread_and_exposure_cap() {
+        let a = Allocation::from_squares(&[20, 21], &[SOL / 10, SOL / 100]);
+        let b = Allocation::uniform(&[20, 21], SOL / 100);
+        let plan = config(PortfolioMode::Spread, None).plan(&deployed(), &[], &[a, b]);
+
+        assert_eq!(plan[0].squares(), vec![0, 1]);
+        assert_eq!(plan[0].amounts()[0], SOL / 10, "largest amount on the thinnest square");
+        assert_eq!(plan[1].squares(), vec![2, 3]);
+
+        let capped = config(PortfolioMode::Off, Some(SOL / 20)).plan(&deployed(), &[], &[a, b]);
+        let total: u64 = capped.iter().map(Allocation::total).sum();
+        assert!(total <= SOL / 20 && total > SOL / 25, "{}", total);
+        // Nothing left once the cap is used up
+        let late = config(PortfolioMode::Off, Some(SOL / 20)).plan(&deployed(), &capped, &[b]);
+        assert_eq!(late[0].total(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_wait_for_each_other() {
+        let portfolio = Portfolio::new(config(PortfolioMode::Deconflict, None));
+        portfolio.join("a");
+        portfolio.join("b");
+        let proposal = Allocation::uniform(&[0], SOL / 100);
+
+        let other = portfolio.clone();
+        let first = tokio::spawn(async move { other.coordinate(7, "a", &deployed(), proposal).await });
+        let second = portfolio.coordinate(7, "b", &deployed(), proposal).await;
+        let first = first.await.unwrap();
+
+        let mut squares = [first.squares(), second.squares()];
+        squares.sort();
+        assert_eq!(squares, [vec![0], vec![1]]);
+        assert_eq!(portfolio.last_round().unwrap().exposure, SOL / 50);
+
+        // A late report for the previous round gets its own proposal back and keeps the plan
+        assert_eq!(portfolio.coordinate(6, "a", &deployed(), proposal).await, proposal);
+        assert_eq!(portfolio.last_round().unwrap().exposure, SOL / 50);
+    }
+}
@@ -0,0 +1,222 @@
+//! ORE Price
+//!
+//! ORE/SOL rates for valuing ORE rewards in EV and P&L. A `PriceSource` is a
+//! static value from config or a feed (a JSON file or an HTTP endpoint, such
+//! as a local stub). `PriceCache` polls a source at most once per refresh
+//! interval and serves the time-weighted average over a trailing window, so
+//! one bad tick doesn't swing every EV number.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use parking_lot::Mutex;
+use reqwest::Client;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// A source of ORE/SOL rates
+pub trait PriceSource: Send + Sync {
+    /// Name of the price source (reported with stats)
+    fn name(&self) -> &str;
+
+    /// SOL per ORE
+    fn fetch(&self) -> BoxFuture<'_, Result<f64>>;
+}
+
+/// Fixed rate from config
+pub struct StaticPrice(pub f64);
+
+impl PriceSource for StaticPrice {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<f64>> {
+        Box::pin(async move { Ok(self.0) })
+    }
+}
+
+/// Rate read from a file path or an `http(s)://` URL
+///
+/// The body is either a bare number or a JSON object with `ore_sol` or
+/// `price` (SOL per ORE).
+pub struct FeedPrice {
+    location: String,
+    client: Client,
+}
+
+impl FeedPrice {
+    pub fn new(location: &str) -> Self {
+        Self {
+            location: location.to_string(),
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl PriceSource for FeedPrice {
+    fn name(&self) -> &str {
+        &self.location
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<f64>> {
+        Box::pin(async move {
+            let body = if self.location.starts_with("http://") || self.location.starts_with("https://") {
+                self.client.get(&self.location).send().await?
+                    .error_for_status()?
+                    .text().await?
+            } else {
+                tokio::fs::read_to_string(&self.location).await
+                    .with_context(|| format!("Failed to read price file {}", self.location))?
+            };
+            parse_price(&body)
+        })
+    }
+}
+
+/// Parse a bare number or `{"ore_sol": ..}` / `{"price": ..}`
+pub fn parse_price(body: &str) -> Result<f64> {
+    let value: serde_json::Value = serde_json::from_str(body.trim()).context("Price feed is not JSON")?;
+    let price = match &value {
+        serde_json::Value::Object(map) => map.get("ore_sol").or_else(|| map.get("price")).and_then(|v| v.as_f64()),
+        other => other.as_f64(),
+    }
+    .context("Price feed has no `ore_sol` or `price` number")?;
+
+    if !price.is_finite() || price < 0.0 {
+        anyhow::bail!("Price feed returned an invalid price: {}", price);
+    }
+    Ok(price)
+}
+
+/// Time-weighted average of `(time, price)` samples over the trailing `window`
+///
+/// Each sample holds until the next one; the newest holds until `now`.
+pub fn time_weighted(samples: &[(Instant, f64)], now: Instant, window: Duration) -> Option<f64> {
+    let start = now.checked_sub(window).unwrap_or(now);
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+    for (i, &(at, price)) in samples.iter().enumerate() {
+        let until = samples.get(i + 1).map(|&(next, _)| next).unwrap_or(now);
+        let from = at.max(start);
+        if until > from {
+            let secs = (until - from).as_secs_f64();
+            weighted += price * secs;
+            total += secs;
+        }
+    }
+    if total > 0.0 {
+        Some(weighted / total)
+    } else {
+        samples.last().map(|&(_, price)| price)
+    }
+}
+
+/// Polls a source and serves its time-weighted average
+#[derive(Clone)]
+pub struct PriceCache {
+    source: Arc<dyn PriceSource>,
+    refresh: Duration,
+    window: Duration,
+    state: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    samples: VecDeque<(Instant, f64)>,
+    last_attempt: Option<Instant>,
+}
+
+impl PriceCache {
+    pub fn new(source: Arc<dyn PriceSource>, refresh: Duration, window: Duration) -> Self {
+        Self {
+            source,
+            refresh,
+            window,
+            state: Arc::new(Mutex::new(CacheState::default())),
+        }
+    }
+
+    /// A cache that always values ORE at zero
+    pub fn zero() -> Self {
+        Self::new(Arc::new(StaticPrice(0.0)), Duration::from_secs(3600), Duration::from_secs(3600))
+    }
+
+    /// Name of the price source (reported with stats)
+    pub fn source_name(&self) -> &str {
+        self.source.name()
+    }
+
+    /// Time-weighted SOL per ORE (0 until the source has answered once)
+    pub async fn ore_sol(&self) -> f64 {
+        let due = match self.state.lock().last_attempt {
+            Some(at) => at.elapsed() >= self.refresh,
+            None => true,
+        };
+        if due {
+            self.state.lock().last_attempt = Some(Instant::now());
+            match self.source.fetch().await {
+                Ok(price) => {
+                    debug!("ORE price from {}: {} SOL", self.source.name(), price);
+                    let now = Instant::now();
+                    let mut state = self.state.lock();
+                    state.samples.push_back((now, price));
+                    // Keep one sample older than the window so the window stays covered
+                    while state.samples.len() > 1 && state.samples[1].0 + self.window <= now {
+                        state.samples.pop_front();
+                    }
+                }
+                Err(e) => warn!("ORE price from {} failed, using cached price: {}", self.source.name(), e),
+            }
+        }
+
+        let state = self.state.lock();
+        let samples: Vec<(Instant, f64)> = state.samples.iter().copied().collect();
+        time_weighted(&samples, Instant::now(), self.window).unwrap_or(0.0)
+    }
+
+    /// Lamports per ORE
+    pub async fn ore_price_lamports(&self) -> u64 {
+        (self.ore_sol().await * 1_000_000_000.0).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(parse_price("0.25").unwrap(), 0.25);
+        assert_eq!(parse_price(r#"{"ore_sol": 1.5}"#).unwrap(), 1.5);
+        assert_eq!(parse_price(r#"{"price": 2}"#).unwrap(), 2.0);
+        assert!(parse_price(r#"{"usd": 2}"#).is_err());
+        assert!(parse_price("-1").is_err());
+    }
+
+    #[test]
+    fn test_time_weighted() {
+        let now = Instant::now();
+        // 1.0 for 30s, then 2.0 for the last 10s of a 40s window
+        let samples = [(now - secs(60), 1.0), (now - secs(10), 2.0)];
+        let twap = time_weighted(&samples, now, secs(40)).unwrap();
+        assert!((twap - 1.25).abs() < 1e-9);
+        assert_eq!(time_weighted(&[], now, secs(40)), None);
+    }
+
+    #[tokio::test]
+    async fn test_cache_serves_static_price() {
+        let cache = PriceCache::new(Arc::new(StaticPrice(0.5)), secs(60), secs(600));
+        assert_eq!(cache.ore_price_lamports().await, 500_000_000);
+        assert_eq!(PriceCache::zero().ore_price_lamports().await, 0);
+    }
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+}
@@ -0,0 +1,254 @@
+//! Transaction Rebroadcast
+//!
+//! Deploys are sent with `skip_preflight` and leaders drop transactions under
+//! load, so a single send often never lands. The rebroadcaster resends the
+//! same signed transaction every few hundred ms until it confirms, its
+//! blockhash expires, or the round's `end_slot` passes. A transaction that's
+//! still in flight when resending stops is watched until its blockhash
+//! expires, so one landing in the round's last slots isn't taken for lost.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use tokio::sync::broadcast;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+use crate::ore::OreClient;
+use crate::sender::TxSender;
+
+/// Rebroadcast settings
+#[derive(Debug, Clone)]
+pub struct RebroadcastConfig {
+    /// Delay between resends
+    pub interval: Duration,
+    /// Check blockhash validity every N ticks (it changes slowly)
+    pub blockhash_check_every: u32,
+}
+
+impl Default for RebroadcastConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(300),
+            blockhash_check_every: 5,
+        }
+    }
+}
+
+/// Why rebroadcasting stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebroadcastOutcome {
+    Confirmed { resends: u32 },
+    /// Landed but the transaction itself failed
+    Failed { error: TransactionError, resends: u32 },
+    BlockhashExpired { resends: u32 },
+    RoundClosed { resends: u32 },
+    Cancelled { resends: u32 },
+}
+
+impl RebroadcastOutcome {
+    /// Status string used in `tx:confirmed` events
+    pub fn status(&self) -> &'static str {
+        match self {
+            Self::Confirmed { .. } => "confirmed",
+            Self::Failed { .. } => "failed",
+            Self::BlockhashExpired { .. } => "expired",
+            Self::RoundClosed { .. } => "round_closed",
+            Self::Cancelled { .. } => "cancelled",
+        }
+    }
+
+    pub fn resends(&self) -> u32 {
+        match self {
+            Self::Confirmed { resends }
+            | Self::Failed { resends, .. }
+            | Self::BlockhashExpired { resends }
+            | Self::RoundClosed { resends }
+            | Self::Cancelled { resends } => *resends,
+        }
+    }
+}
+
+/// Rebroadcast counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RebroadcastStats {
+    pub confirmed: u64,
+    pub failed: u64,
+    pub expired: u64,
+    pub round_closed: u64,
+    pub cancelled: u64,
+    /// Resends needed per landing -> number of landings
+    pub resends_per_landing: BTreeMap<u32, u64>,
+}
+
+impl RebroadcastStats {
+    fn record(&mut self, outcome: &RebroadcastOutcome) {
+        match outcome {
+            RebroadcastOutcome::Confirmed { resends } => {
+                self.confirmed += 1;
+                *self.resends_per_landing.entry(*resends).or_default() += 1;
+            }
+            RebroadcastOutcome::Failed { resends, .. } => {
+                self.failed += 1;
+                *self.resends_per_landing.entry(*resends).or_default() += 1;
+            }
+            RebroadcastOutcome::BlockhashExpired { .. } => self.expired += 1,
+            RebroadcastOutcome::RoundClosed { .. } => self.round_closed += 1,
+            RebroadcastOutcome::Cancelled { .. } => self.cancelled += 1,
+        }
+    }
+}
+
+/// Resends signed transactions until they land or can no longer land
+#[derive(Clone)]
+pub struct Rebroadcaster {
+    ore_client: OreClient,
+    sender: TxSender,
+    config: RebroadcastConfig,
+    stats: Arc<RwLock<RebroadcastStats>>,
+}
+
+impl Rebroadcaster {
+    /// Create a new rebroadcaster
+    pub fn new(ore_client: OreClient, sender: TxSender, config: RebroadcastConfig) -> Self {
+        Self {
+            ore_client,
+            sender,
+            config,
+            stats: Arc::new(RwLock::new(RebroadcastStats::default())),
+        }
+    }
+
+    /// Rebroadcast counters
+    pub fn stats(&self) -> RebroadcastStats {
+        self.stats.read().clone()
+    }
+
+    /// Resend `tx` until it confirms, its blockhash expires, `end_slot`
+    /// passes, or `cancel_rx` fires. The receiver is borrowed so a stop sent
+    /// before or between runs is still waiting in it
+    pub async fn run(
+        &self,
+        tx: &Transaction,
+        end_slot: u64,
+        cancel_rx: &mut broadcast::Receiver<()>,
+    ) -> RebroadcastOutcome {
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+        let mut resends: u32 = 0;
+        let mut tick: u32 = 0;
+
+        let outcome = loop {
+            tokio::select! {
+                _ = cancel_rx.recv() => {
+                    // It may have landed since the last look
+                    break self.check_landed(&signature, resends).await
+                        .unwrap_or(RebroadcastOutcome::Cancelled { resends });
+                }
+                _ = sleep(self.config.interval) => {}
+            }
+            tick += 1;
+
+            if let Some(outcome) = self.check_landed(&signature, resends).await {
+                break outcome;
+            }
+
+            if let Ok(slot) = self.ore_client.get_slot().await {
+                if slot > end_slot {
+                    // One last look - it may have landed in the final slot
+                    break self.check_landed(&signature, resends).await
+                        .unwrap_or(RebroadcastOutcome::RoundClosed { resends });
+                }
+            }
+
+            if tick % self.config.blockhash_check_every == 0 {
+                let valid = self.ore_client.rpc()
+                    .is_blockhash_valid(&tx.message.recent_blockhash, CommitmentConfig::processed())
+                    .await
+                    .unwrap_or(true);
+                if !valid {
+                    break self.check_landed(&signature, resends).await
+                        .unwrap_or(RebroadcastOutcome::BlockhashExpired { resends });
+                }
+            }
+
+            let accepted = self.sender.resend(tx).await;
+            resends += 1;
+            debug!("Rebroadcast #{} of {} accepted by {} path(s)", resends, signature, accepted);
+        };
+
+        match &outcome {
+            RebroadcastOutcome::Confirmed { resends } => {
+                info!("Transaction {} confirmed after {} resend(s)", signature, resends);
+            }
+            other => warn!("Stopped rebroadcasting {}: {} after {} resend(s)", signature, other.status(), other.resends()),
+        }
+        self.stats.write().record(&outcome);
+        outcome
+    }
+
+    /// Watch a transaction that's no longer being resent until it lands or
+    /// its blockhash expires. None means it can no longer land
+    pub async fn watch(&self, tx: &Transaction, resends: u32) -> Option<RebroadcastOutcome> {
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+        let mut tick: u32 = 0;
+
+        loop {
+            sleep(self.config.interval).await;
+            tick += 1;
+
+            if let Some(outcome) = self.check_landed(&signature, resends).await {
+                return Some(outcome);
+            }
+            if tick % self.config.blockhash_check_every == 0 {
+                let valid = self.ore_client.rpc()
+                    .is_blockhash_valid(&tx.message.recent_blockhash, CommitmentConfig::processed())
+                    .await
+                    .unwrap_or(true);
+                if !valid {
+                    // One last look before giving up on it
+                    return self.check_landed(&signature, resends).await;
+                }
+            }
+        }
+    }
+
+    /// Confirmed or failed status, if the transaction has landed
+    async fn check_landed(&self, signature: &Signature, resends: u32) -> Option<RebroadcastOutcome> {
+        let statuses = self.ore_client.rpc()
+            .get_signature_statuses(std::slice::from_ref(signature))
+            .await
+            .ok()?;
+        let status = statuses.value.into_iter().next().flatten()?;
+
+        if let Some(error) = status.err {
+            return Some(RebroadcastOutcome::Failed { error, resends });
+        }
+        status.satisfies_commitment(CommitmentConfig::confirmed())
+            .then_some(RebroadcastOutcome::Confirmed { resends })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_track_resends_per_landing() {
+        let mut stats = RebroadcastStats::default();
+        stats.record(&RebroadcastOutcome::Confirmed { resends: 2 });
+        stats.record(&RebroadcastOutcome::Confirmed { resends: 2 });
+        stats.record(&RebroadcastOutcome::BlockhashExpired { resends: 40 });
+
+        assert_eq!(stats.confirmed, 2);
+        assert_eq!(stats.expired, 1);
+        assert_eq!(stats.resends_per_landing.get(&2), Some(&2));
+        assert_eq!(stats.resends_per_landing.get(&40), None);
+    }
+}
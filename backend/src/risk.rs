@@ -0,0 +1,242 @@
+//! Risk Controls
+//!
+//! Time-based limits on top of the per-session budget, checked by the engine
+//! before every deploy:
+//!
+//! - a maximum net loss per wallet over a rolling 24 hours
+//! - a cooldown of N rounds after K consecutive losing rounds
+//! - a global kill switch, engaged when the combined drawdown of all wallets
+//!   exceeds a threshold (or by hand); until it's reset, every session keeps
+//!   running but skips its rounds and nothing is sent
+//!
+//! Cooldowns and the kill switch live in Postgres, so restarts don't reset
+//! them; losses and drawdown are computed from settled deploys.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::db::Database;
+
+/// Scope of the global kill switch row
+const GLOBAL: &str = "global";
+
+/// Risk limits (lamports); `None` disables a limit
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RiskLimits {
+    /// Net loss per wallet over the last 24 hours
+    pub max_daily_loss: Option<u64>,
+    /// Consecutive losing rounds that start a cooldown
+    pub loss_streak: Option<u32>,
+    /// Rounds a cooldown lasts
+    pub cooldown_rounds: u64,
+    /// Combined drawdown of all wallets that engages the kill switch
+    pub max_drawdown: Option<u64>,
+}
+
+/// What the risk checks saw for a wallet
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RiskFacts {
+    pub kill_switch: bool,
+    pub kill_reason: Option<String>,
+    /// Lamports below the peak of combined P&L since the last reset
+    pub drawdown: i64,
+    /// Net lamports lost by the wallet in the last 24 hours
+    pub daily_loss: i64,
+    /// Consecutive losing rounds since the wallet's last win or cooldown
+    pub loss_streak: u32,
+    /// Round the wallet's cooldown ends at
+    pub cooldown_until: Option<u64>,
+}
+
+/// Outcome of a risk check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskAction {
+    Allow,
+    /// Skip the round
+    Block(String),
+    /// Start a cooldown ending at `until_round` and skip the round
+    Cooldown { until_round: u64, reason: String },
+    /// Engage the kill switch and skip the round
+    Kill(String),
+}
+
+fn sol(lamports: i64) -> f64 {
+    lamports as f64 / 1_000_000_000.0
+}
+
+impl RiskLimits {
+    /// Judge a round for a wallet
+    pub fn judge(&self, facts: &RiskFacts, round_id: u64) -> RiskAction {
+        if facts.kill_switch {
+            return RiskAction::Block(format!(
+                "Kill switch engaged: {}",
+                facts.kill_reason.as_deref().unwrap_or("no reason given")
+            ));
+        }
+        if let Some(max) = self.max_drawdown.filter(|&max| facts.drawdown > max as i64) {
+            return RiskAction::Kill(format!(
+                "drawdown {:.4} SOL exceeds {:.4} SOL", sol(facts.drawdown), sol(max as i64)
+            ));
+        }
+        if let Some(until) = facts.cooldown_until.filter(|&until| until > round_id) {
+            return RiskAction::Block(format!("Cooling down until round {}", until));
+        }
+        if let Some(k) = self.loss_streak.filter(|&k| k > 0 && facts.loss_streak >= k) {
+            return RiskAction::Cooldown {
+                until_round: round_id + self.cooldown_rounds,
+                reason: format!("{} consecutive losses (limit {})", facts.loss_streak, k),
+            };
+        }
+        if let Some(max) = self.max_daily_loss.filter(|&max| facts.daily_loss >= max as i64) {
+            return RiskAction::Block(format!(
+                "24h loss {:.4} SOL reached the {:.4} SOL cap", sol(facts.daily_loss), sol(max as i64)
+            ));
+        }
+        RiskAction::Allow
+    }
+}
+
+/// Checks and persists risk state
+#[derive(Clone)]
+pub struct RiskManager {
+    db: Database,
+    limits: RiskLimits,
+    /// Mirror of the kill switch, for a last check right before sending
+    killed: Arc<AtomicBool>,
+}
+
+impl RiskManager {
+    /// Load the persisted kill switch; on first run, drawdown is measured from now
+    pub async fn load(db: Database, limits: RiskLimits) -> Result<Self> {
+        let global = match db.get_risk_state(GLOBAL).await? {
+            Some(global) => global,
+            None => db.set_kill_switch(false, None).await?,
+        };
+        let killed = global.blocked;
+        if killed {
+            warn!("Kill switch is engaged - sessions will skip every round until it is reset");
+        }
+        Ok(Self { db, limits, killed: Arc::new(AtomicBool::new(killed)) })
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Whether the kill switch is engaged
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+
+    /// Current risk facts for a wallet
+    pub async fn facts(&self, wallet: &str) -> Result<RiskFacts> {
+        let global = self.db.get_risk_state(GLOBAL).await?;
+        let cooldown = self.db.get_risk_state(wallet).await?;
+        let baseline = global.as_ref().map(|g| g.baseline_at).unwrap_or_else(Utc::now);
+        let cooldown_until = cooldown.and_then(|c| c.until_round).map(|r| r.max(0) as u64);
+
+        let kill_switch = global.as_ref().is_some_and(|g| g.blocked);
+        self.killed.store(kill_switch, Ordering::Relaxed);
+        Ok(RiskFacts {
+            kill_switch,
+            kill_reason: global.and_then(|g| g.reason),
+            drawdown: self.db.get_drawdown_since(baseline).await?,
+            daily_loss: self.db.get_net_loss_since(wallet, Utc::now() - Duration::hours(24)).await?,
+            loss_streak: self.db.get_wallet_loss_streak(wallet, cooldown_until.unwrap_or(0) as i64).await? as u32,
+            cooldown_until,
+        })
+    }
+
+    /// Check a wallet's round, persisting any cooldown or kill it triggers.
+    /// Returns the reason to skip; if the state can't be read the round is skipped.
+    pub async fn check(&self, wallet: &str, round_id: u64) -> Option<String> {
+        let facts = match self.facts(wallet).await {
+            Ok(facts) => facts,
+            Err(e) => {
+                warn!("Risk state unavailable for {}: {}", wallet, e);
+                return Some(format!("Risk state unavailable: {}", e));
+            }
+        };
+        match self.limits.judge(&facts, round_id) {
+            RiskAction::Allow => None,
+            RiskAction::Block(reason) => Some(reason),
+            RiskAction::Cooldown { until_round, reason } => {
+                warn!("Cooldown for {} until round {}: {}", wallet, until_round, reason);
+                if let Err(e) = self.db.set_cooldown(wallet, until_round as i64, &reason).await {
+                    warn!("Failed to persist cooldown: {}", e);
+                }
+                Some(format!("Cooldown until round {}: {}", until_round, reason))
+            }
+            RiskAction::Kill(reason) => {
+                error!("Engaging kill switch: {}", reason);
+                if let Err(e) = self.kill(&reason).await {
+                    warn!("Failed to persist kill switch: {}", e);
+                }
+                Some(format!("Kill switch engaged: {}", reason))
+            }
+        }
+    }
+
+    /// Engage the kill switch
+    pub async fn kill(&self, reason: &str) -> Result<()> {
+        self.killed.store(true, Ordering::Relaxed);
+        self.db.set_kill_switch(true, Some(reason)).await?;
+        Ok(())
+    }
+
+    /// Clear the kill switch and measure drawdown from now on
+    pub async fn reset(&self) -> Result<()> {
+        self.db.set_kill_switch(false, None).await?;
+        self.killed.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn limits() -> RiskLimits {
+        RiskLimits {
+            max_daily_loss: Some(2 * SOL),
+            loss_streak: Some(3),
+            cooldown_rounds: 5,
+            max_drawdown: Some(10 * SOL),
+        }
+    }
+
+    #[test]
+    fn test_limits_in_priority_order() {
+        let limits = limits();
+        assert_eq!(limits.judge(&RiskFacts::default(), 100), RiskAction::Allow);
+
+        let streak = RiskFacts { loss_streak: 3, ..Default::default() };
+        assert!(matches!(limits.judge(&streak, 100), RiskAction::Cooldown { until_round: 105, .. }));
+
+        let cooling = RiskFacts { cooldown_until: Some(105), ..Default::default() };
+        assert!(matches!(limits.judge(&cooling, 104), RiskAction::Block(_)));
+        assert_eq!(limits.judge(&cooling, 105), RiskAction::Allow);
+
+        let daily = RiskFacts { daily_loss: 2 * SOL as i64, ..Default::default() };
+        assert!(matches!(limits.judge(&daily, 100), RiskAction::Block(r) if r.contains("24h")));
+
+        // Drawdown beats everything but an engaged kill switch
+        let drawdown = RiskFacts { drawdown: 11 * SOL as i64, loss_streak: 3, ..Default::default() };
+        assert!(matches!(limits.judge(&drawdown, 100), RiskAction::Kill(_)));
+        let killed = RiskFacts { kill_switch: true, ..drawdown };
+        assert!(matches!(limits.judge(&killed, 100), RiskAction::Block(r) if r.starts_with("Kill switch")));
+    }
+
+    #[test]
+    fn test_disabled_limits_allow() {
+        let facts = RiskFacts { drawdown: i64::MAX, daily_loss: i64::MAX, loss_streak: 99, ..Default::default() };
+        assert_eq!(RiskLimits::default().judge(&facts, 1), RiskAction::Allow);
+    }
+}
@@ -0,0 +1,170 @@
+//! Skip/Play Rules
+//!
+//! Declarative per-session rules, configured as JSON when the session starts:
+//!
+//! ```json
+//! [{"rule": "min_pot", "sol": 1.5},
+//!  {"rule": "min_miners", "count": 20},
+//!  {"rule": "min_best_ev", "sol": 0.0},
+//!  {"rule": "max_loss_streak", "losses": 5}]
+//! ```
+//!
+//! Every rule is evaluated each round and its verdict reported with the
+//! decision; the round is played only if all of them pass.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A single skip/play rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Rule {
+    /// Skip if the round's pot is below `sol`
+    MinPot { sol: f64 },
+    /// Skip if fewer than `count` miners are in the round
+    MinMiners { count: u64 },
+    /// Play only if the best square's EV is above `sol`
+    MinBestEv { sol: f64 },
+    /// Skip after more than `losses` consecutive losing rounds
+    MaxLossStreak { losses: u32 },
+}
+
+/// Round facts the rules look at
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleInput {
+    /// Lamports deployed in the round
+    pub pot: u64,
+    pub miners: u64,
+    /// Best single-square EV (lamports)
+    pub best_ev: f64,
+    /// Consecutive losing rounds so far; None if unknown
+    pub loss_streak: Option<u32>,
+}
+
+/// One rule's verdict for a round
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleVerdict {
+    pub rule: String,
+    pub pass: bool,
+    pub detail: String,
+}
+
+fn sol(lamports: f64) -> f64 {
+    lamports / 1_000_000_000.0
+}
+
+impl Rule {
+    fn name(&self) -> &'static str {
+        match self {
+            Rule::MinPot { .. } => "min_pot",
+            Rule::MinMiners { .. } => "min_miners",
+            Rule::MinBestEv { .. } => "min_best_ev",
+            Rule::MaxLossStreak { .. } => "max_loss_streak",
+        }
+    }
+
+    /// Judge a round
+    pub fn evaluate(&self, input: &RuleInput) -> RuleVerdict {
+        let (pass, detail) = match *self {
+            Rule::MinPot { sol: min } => {
+                let pot = sol(input.pot as f64);
+                (pot >= min, format!("pot {:.4} SOL vs min {:.4} SOL", pot, min))
+            }
+            Rule::MinMiners { count } => {
+                (input.miners >= count, format!("{} miners vs min {}", input.miners, count))
+            }
+            Rule::MinBestEv { sol: min } => {
+                let best = sol(input.best_ev);
+                (best > min, format!("best EV {:.6} SOL vs min {:.6} SOL", best, min))
+            }
+            Rule::MaxLossStreak { losses } => match input.loss_streak {
+                Some(streak) => (streak <= losses, format!("{} consecutive losses vs max {}", streak, losses)),
+                // Don't sit out rounds just because the history is unavailable
+                None => (true, "loss streak unknown".to_string()),
+            },
+        };
+        RuleVerdict { rule: self.name().to_string(), pass, detail }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match *self {
+            Rule::MinPot { sol } if !sol.is_finite() || sol < 0.0 => anyhow::bail!("min_pot must be >= 0 (SOL)"),
+            Rule::MinBestEv { sol } if !sol.is_finite() => anyhow::bail!("min_best_ev must be a number (SOL)"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A session's rules
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RuleSet(pub Vec<Rule>);
+
+impl RuleSet {
+    pub fn validate(&self) -> Result<()> {
+        self.0.iter().try_for_each(Rule::validate)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether any rule needs the session's loss streak
+    pub fn needs_loss_streak(&self) -> bool {
+        self.0.iter().any(|r| matches!(r, Rule::MaxLossStreak { .. }))
+    }
+
+    /// Every rule's verdict, in order
+    pub fn evaluate(&self, input: &RuleInput) -> Vec<RuleVerdict> {
+        self.0.iter().map(|rule| rule.evaluate(input)).collect()
+    }
+}
+
+/// Skip reason naming the failed rules, if any failed
+pub fn skip_reason(verdicts: &[RuleVerdict]) -> Option<String> {
+    let failed: Vec<String> = verdicts.iter()
+        .filter(|v| !v.pass)
+        .map(|v| format!("{} ({})", v.rule, v.detail))
+        .collect();
+    if failed.is_empty() {
+        None
+    } else {
+        Some(format!("Rules failed: {}", failed.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_from_json() {
+        let rules: RuleSet = serde_json::from_str(
+            r#"[{"rule": "min_pot", "sol": 1.5}, {"rule": "min_miners", "count": 20},
+                {"rule": "min_best_ev", "sol": 0.0}, {"rule": "max_loss_streak", "losses": 3}]"#,
+        ).unwrap();
+        assert_eq!(rules.0.len(), 4);
+        assert!(rules.validate().is_ok() && rules.needs_loss_streak());
+        assert!(serde_json::from_str::<RuleSet>(r#"[{"rule": "min_vibes"}]"#).is_err());
+        assert!(RuleSet(vec![Rule::MinPot { sol: -1.0 }]).validate().is_err());
+    }
+
+    #[test]
+    fn test_every_rule_gets_a_verdict() {
+        let rules = RuleSet(vec![
+            Rule::MinPot { sol: 1.0 },
+            Rule::MinMiners { count: 10 },
+            Rule::MinBestEv { sol: 0.0 },
+            Rule::MaxLossStreak { losses: 2 },
+        ]);
+        let input = RuleInput { pot: 2_000_000_000, miners: 5, best_ev: 1_000.0, loss_streak: Some(3) };
+        let verdicts = rules.evaluate(&input);
+        assert_eq!(verdicts.iter().map(|v| v.pass).collect::<Vec<_>>(), vec![true, false, true, false]);
+
+        let reason = skip_reason(&verdicts).unwrap();
+        assert!(reason.contains("min_miners") && reason.contains("max_loss_streak") && !reason.contains("min_pot"));
+        assert_eq!(skip_reason(&verdicts[..1]), None);
+        // Unknown history never skips
+        assert!(Rule::MaxLossStreak { losses: 0 }.evaluate(&RuleInput::default()).pass);
+    }
+}
@@ -0,0 +1,348 @@
+//! Session Scheduler
+//!
+//! Per-wallet schedules, stored in Postgres, that start and stop mining
+//! sessions on their own. A schedule is either a time window (mine between
+//! two UTC times, optionally on given weekdays) or a 5-field cron expression
+//! that starts a run at each matching minute. A run ends when its window
+//! closes, after `max_rounds` rounds, after a cron run's `minutes`, or when
+//! the schedule's `until` date passes, which also disables the schedule.
+
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::ScheduleRecord;
+
+/// When a schedule mines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleSpec {
+    /// Mine between `start` and `end` ("HH:MM", UTC); may run past midnight
+    Window {
+        start: String,
+        end: String,
+        /// Weekdays the window opens on (0 = Sunday); empty = every day
+        #[serde(default)]
+        days: Vec<u8>,
+    },
+    /// Start a run at every minute matching `expr` (UTC)
+    Cron {
+        expr: String,
+        /// Run length; without it a run lasts `max_rounds` rounds
+        #[serde(default)]
+        minutes: Option<u32>,
+    },
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+        .map_err(|_| anyhow::anyhow!("invalid time {:?}, expected HH:MM", s))
+}
+
+/// A 5-field cron expression: minute hour day-of-month month day-of-week.
+/// Fields take `*`, numbers, ranges (`9-17`), lists (`1,15`) and steps (`*/5`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Bitmask of the values a cron field allows
+fn cron_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>()?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            anyhow::bail!("cron step must be > 0");
+        }
+        let (lo, hi) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((lo, hi)) => (lo.parse()?, hi.parse()?),
+            // `5/15` means from 5 to the end, every 15
+            None if step > 1 => (range.parse()?, max),
+            None => {
+                let value = range.parse()?;
+                (value, value)
+            }
+        };
+        if lo < min || hi > max || lo > hi {
+            anyhow::bail!("cron field {:?} out of range {}-{}", part, min, max);
+        }
+        for value in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl FromStr for Cron {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            anyhow::bail!("cron expression needs 5 fields: minute hour day month weekday");
+        }
+        // 7 is Sunday too
+        let mut weekdays = cron_field(fields[4], 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: cron_field(fields[0], 0, 59)?,
+            hours: cron_field(fields[1], 0, 23)?,
+            days: cron_field(fields[2], 1, 31)?,
+            months: cron_field(fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+}
+
+impl Cron {
+    /// Whether the minute `at` falls in matches
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let has = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day = has(self.days, at.day());
+        let weekday = has(self.weekdays, at.weekday().num_days_from_sunday());
+        // As in cron, when both day fields are restricted either one may match
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        };
+        has(self.minutes, at.minute()) && has(self.hours, at.hour()) && has(self.months, at.month()) && day_matches
+    }
+}
+
+/// A stored schedule
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    pub id: Uuid,
+    pub wallet: String,
+    pub spec: ScheduleSpec,
+    /// `/api/session/start` body each run is started with
+    pub session: serde_json::Value,
+    /// Rounds after which a run stops
+    pub max_rounds: Option<i64>,
+    /// The schedule ends at this time
+    pub until: Option<DateTime<Utc>>,
+    pub enabled: bool,
+    /// Session of the run in progress
+    pub session_id: Option<Uuid>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A run in progress
+#[derive(Debug, Clone, Copy)]
+pub struct Run {
+    pub started_at: DateTime<Utc>,
+    /// Rounds the run's session played or sat out
+    pub rounds: i64,
+}
+
+/// What the scheduler does with a schedule now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleAction {
+    Wait,
+    Start,
+    /// End the run in progress
+    Stop(String),
+    /// The schedule is over: end any run and disable it
+    Expire,
+}
+
+impl Schedule {
+    /// Decode a stored schedule
+    pub fn from_record(record: ScheduleRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id,
+            wallet: record.wallet,
+            spec: serde_json::from_value(record.spec)?,
+            session: record.session,
+            max_rounds: record.max_rounds,
+            until: record.until_at,
+            enabled: record.enabled,
+            session_id: record.session_id,
+            last_started_at: record.last_started_at,
+            created_at: record.created_at,
+        })
+    }
+
+    /// Check the spec and limits before saving
+    pub fn validate(&self) -> Result<()> {
+        match &self.spec {
+            ScheduleSpec::Window { start, end, days } => {
+                if parse_time(start)? == parse_time(end)? {
+                    anyhow::bail!("window start and end must differ");
+                }
+                if days.iter().any(|&d| d > 6) {
+                    anyhow::bail!("window days must be 0-6 (0 = Sunday)");
+                }
+            }
+            ScheduleSpec::Cron { expr, minutes } => {
+                expr.parse::<Cron>()?;
+                if minutes.is_none() && self.max_rounds.is_none() {
+                    anyhow::bail!("a cron schedule needs minutes or max_rounds to end its runs");
+                }
+                if *minutes == Some(0) {
+                    anyhow::bail!("minutes must be > 0");
+                }
+            }
+        }
+        if self.max_rounds.is_some_and(|n| n <= 0) {
+            anyhow::bail!("max_rounds must be > 0");
+        }
+        Ok(())
+    }
+
+    /// When the occurrence `at` falls in opened: the window's start, or the
+    /// matching cron minute. None outside the schedule
+    fn opened(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.spec {
+            ScheduleSpec::Window { start, end, days } => {
+                let (start, end) = (parse_time(start).ok()?, parse_time(end).ok()?);
+                let (today, now) = (at.date_naive(), at.time());
+                let opened_on = if start < end {
+                    (now >= start && now < end).then_some(today)?
+                } else if now >= start {
+                    today
+                } else if now < end {
+                    // Overnight window that opened yesterday
+                    today.pred_opt()?
+                } else {
+                    return None;
+                };
+                if !days.is_empty() && !days.contains(&(opened_on.weekday().num_days_from_sunday() as u8)) {
+                    return None;
+                }
+                Some(opened_on.and_time(start).and_utc())
+            }
+            ScheduleSpec::Cron { expr, .. } => {
+                let cron: Cron = expr.parse().ok()?;
+                cron.matches(at).then(|| at.with_second(0)?.with_nanosecond(0))?
+            }
+        }
+    }
+
+    /// Next action at `now`, given the run in progress
+    pub fn next_action(&self, now: DateTime<Utc>, run: Option<&Run>) -> ScheduleAction {
+        if self.until.is_some_and(|until| now >= until) {
+            return ScheduleAction::Expire;
+        }
+        match run {
+            Some(run) => {
+                if let Some(max) = self.max_rounds.filter(|&max| run.rounds >= max) {
+                    return ScheduleAction::Stop(format!("ran {} rounds", max));
+                }
+                match &self.spec {
+                    ScheduleSpec::Window { .. } if self.opened(now).is_none() => {
+                        ScheduleAction::Stop("window closed".to_string())
+                    }
+                    ScheduleSpec::Cron { minutes: Some(minutes), .. }
+                        if now - run.started_at >= Duration::minutes(*minutes as i64) =>
+                    {
+                        ScheduleAction::Stop(format!("ran {} minutes", minutes))
+                    }
+                    _ => ScheduleAction::Wait,
+                }
+            }
+            // Each window or cron minute starts at most one run
+            None => match self.opened(now) {
+                Some(opened) if self.last_started_at.filter(|&last| last >= opened).is_none() => ScheduleAction::Start,
+                _ => ScheduleAction::Wait,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // October 2026: the 18th is a Sunday
+        Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 30).unwrap()
+    }
+
+    fn schedule(spec: ScheduleSpec, max_rounds: Option<i64>) -> Schedule {
+        Schedule {
+            id: Uuid::new_v4(),
+            wallet: "wallet".to_string(),
+            spec,
+            session: serde_json::Value::Null,
+            max_rounds,
+            until: None,
+            enabled: true,
+            session_id: None,
+            last_started_at: None,
+            created_at: at(1, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_cron_fields() {
+        let cron: Cron = "*/15 9-17 * * 1-5".parse().unwrap();
+        assert!(cron.matches(at(19, 9, 45)));
+        assert!(!cron.matches(at(19, 9, 50)));
+        assert!(!cron.matches(at(18, 9, 45)), "Sunday");
+        // Sunday as 7, and either day field matching
+        let cron: Cron = "0 0 1 * 7".parse().unwrap();
+        assert!(cron.matches(at(18, 0, 0)) && cron.matches(at(1, 0, 0)) && !cron.matches(at(19, 0, 0)));
+        assert!("* * *".parse::<Cron>().is_err());
+        assert!("60 * * * *".parse::<Cron>().is_err());
+    }
+
+    #[test]
+    fn test_window_starts_once_and_stops_at_close() {
+        let spec = ScheduleSpec::Window { start: "22:00".into(), end: "02:00".into(), days: vec![0] };
+        let mut window = schedule(spec, Some(100));
+        assert!(window.validate().is_ok());
+        assert_eq!(window.next_action(at(18, 23, 0), None), ScheduleAction::Start);
+        // Still the Sunday window after midnight; closed on Monday night
+        assert_eq!(window.next_action(at(19, 1, 0), None), ScheduleAction::Start);
+        assert_eq!(window.next_action(at(19, 23, 0), None), ScheduleAction::Wait);
+
+        window.last_started_at = Some(at(18, 22, 5));
+        assert_eq!(window.next_action(at(18, 23, 0), None), ScheduleAction::Wait, "already ran this window");
+        let run = Run { started_at: at(18, 22, 5), rounds: 10 };
+        assert_eq!(window.next_action(at(18, 23, 0), Some(&run)), ScheduleAction::Wait);
+        assert!(matches!(window.next_action(at(19, 2, 0), Some(&run)), ScheduleAction::Stop(_)));
+        let done = Run { rounds: 100, ..run };
+        assert!(matches!(window.next_action(at(18, 23, 0), Some(&done)), ScheduleAction::Stop(r) if r.contains("100 rounds")));
+    }
+
+    #[test]
+    fn test_cron_runs_and_until() {
+        let spec = ScheduleSpec::Cron { expr: "0 12 * * *".into(), minutes: Some(30) };
+        let mut cron = schedule(spec, None);
+        assert!(cron.validate().is_ok());
+        assert_eq!(cron.next_action(at(20, 12, 0), None), ScheduleAction::Start);
+        assert_eq!(cron.next_action(at(20, 12, 1), None), ScheduleAction::Wait);
+
+        let run = Run { started_at: at(20, 12, 0), rounds: 3 };
+        assert_eq!(cron.next_action(at(20, 12, 20), Some(&run)), ScheduleAction::Wait);
+        assert!(matches!(cron.next_action(at(20, 12, 31), Some(&run)), ScheduleAction::Stop(_)));
+
+        cron.until = Some(at(21, 0, 0));
+        assert_eq!(cron.next_action(at(21, 12, 0), None), ScheduleAction::Expire);
+        cron.spec = ScheduleSpec::Cron { expr: "0 12 * * *".into(), minutes: None };
+        assert!(cron.validate().is_err(), "a run needs an end");
+    }
+}
@@ -0,0 +1,349 @@
+//! Strategy Scripts
+//!
+//! Sandboxed Rhai scripts that pick squares without recompiling the backend.
+//! A script defines `fn select(ctx)`; `ctx` holds the grid (`blocks`), pot,
+//! motherlode, slots remaining, session defaults and session stats. It returns a map with `squares` (and
+//! optionally `amounts` in lamports per square), `#{ skip: true, reason: ".." }`,
+//! or `()` to skip. Every run is bounded by an operation count and a
+//! wall-clock limit, and scripts are swapped between rounds.
+//!
+//! ```rhai
+//! fn select(ctx) {
+//!     let best = ctx.blocks[0];
+//!     for b in ctx.blocks { if b.deployed < best.deployed { best = b; } }
+//!     if ctx.pot < 100_000_000 { return #{ skip: true, reason: "pot too small" }; }
+//!     #{ squares: [best.index] }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::ai::{equal_weights, AiSelection};
+use crate::db::SessionStats;
+
+/// Largest per-square amount a script may request (10 SOL, the session limit)
+const MAX_AMOUNT_PER_SQUARE: u64 = 10_000_000_000;
+
+/// Resource limits for one script run
+#[derive(Debug, Clone)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub timeout: Duration,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 500_000,
+            timeout: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Everything a script sees about the round
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptContext {
+    pub round_id: u64,
+    /// Lamports deployed and miner count per square
+    pub deployed: Vec<u64>,
+    pub miner_counts: Vec<u64>,
+    pub pot: u64,
+    pub motherlode: u64,
+    pub slots_remaining: u64,
+    /// Session defaults, in lamports
+    pub deploy_amount: u64,
+    pub num_blocks: u8,
+    pub max_tip: u64,
+    /// Last round's tip
+    pub tip_cost: u64,
+    pub stats: Option<SessionStats>,
+}
+
+impl ScriptContext {
+    /// Flat grid for validating scripts without a live round
+    pub fn sample() -> Self {
+        Self {
+            round_id: 0,
+            deployed: (0..25).map(|i| 10_000_000 * (i % 7 + 1)).collect(),
+            miner_counts: (0..25).map(|i| i % 5 + 1).collect(),
+            pot: (0..25).map(|i| 10_000_000 * (i % 7 + 1)).sum(),
+            motherlode: 0,
+            slots_remaining: 10,
+            deploy_amount: 1_000_000,
+            num_blocks: 1,
+            max_tip: 1_000_000,
+            tip_cost: 100_000,
+            stats: None,
+        }
+    }
+
+    fn to_map(&self) -> Map {
+        let int = |v: u64| Dynamic::from(v.min(i64::MAX as u64) as i64);
+        let blocks: Array = (0..25)
+            .map(|i| {
+                let mut block = Map::new();
+                block.insert("index".into(), Dynamic::from(i as i64));
+                block.insert("deployed".into(), int(self.deployed.get(i).copied().unwrap_or(0)));
+                block.insert("miners".into(), int(self.miner_counts.get(i).copied().unwrap_or(0)));
+                Dynamic::from_map(block)
+            })
+            .collect();
+
+        let mut stats = Map::new();
+        if let Some(s) = &self.stats {
+            stats.insert("rounds_played".into(), Dynamic::from(s.rounds_played));
+            stats.insert("rounds_skipped".into(), Dynamic::from(s.rounds_skipped));
+            stats.insert("rounds_won".into(), Dynamic::from(s.rounds_won));
+            stats.insert("rounds_lost".into(), Dynamic::from(s.rounds_lost));
+            stats.insert("total_deployed".into(), Dynamic::from(s.total_deployed));
+            stats.insert("net_pnl".into(), Dynamic::from(s.net_pnl));
+            stats.insert("win_rate".into(), Dynamic::from(s.win_rate));
+        }
+
+        let mut ctx = Map::new();
+        ctx.insert("round_id".into(), int(self.round_id));
+        ctx.insert("blocks".into(), Dynamic::from_array(blocks));
+        ctx.insert("pot".into(), int(self.pot));
+        ctx.insert("motherlode".into(), int(self.motherlode));
+        ctx.insert("slots_remaining".into(), int(self.slots_remaining));
+        ctx.insert("deploy_amount".into(), int(self.deploy_amount));
+        ctx.insert("num_blocks".into(), Dynamic::from(self.num_blocks as i64));
+        ctx.insert("max_tip".into(), int(self.max_tip));
+        ctx.insert("tip_cost".into(), int(self.tip_cost));
+        ctx.insert("stats".into(), Dynamic::from_map(stats));
+        ctx
+    }
+}
+
+/// What a script decided
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScriptDecision {
+    Deploy { squares: Vec<u8>, amounts: Vec<u64> },
+    Skip { reason: String },
+}
+
+/// A run's decision and cost
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptRun {
+    pub decision: ScriptDecision,
+    pub operations: u64,
+    pub elapsed_us: u64,
+}
+
+/// A compiled script and its version
+pub struct CompiledScript {
+    pub source: String,
+    pub version: i32,
+    ast: AST,
+}
+
+/// Compiles and runs scripts, and holds each session's current script
+#[derive(Clone)]
+pub struct ScriptRunner {
+    limits: ScriptLimits,
+    scripts: Arc<RwLock<HashMap<Uuid, Arc<CompiledScript>>>>,
+}
+
+impl ScriptRunner {
+    /// Create a runner with the given limits
+    pub fn new(limits: ScriptLimits) -> Self {
+        Self {
+            limits,
+            scripts: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Sandboxed engine whose runs stop at the operation or time limit
+    fn engine(&self, operations: Arc<AtomicU64>) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(self.limits.max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(4_096);
+        engine.set_max_array_size(1_024);
+        engine.set_max_map_size(256);
+        engine.disable_symbol("eval");
+
+        let started = Instant::now();
+        let timeout = self.limits.timeout;
+        engine.on_progress(move |ops| {
+            operations.store(ops, Ordering::Relaxed);
+            (started.elapsed() > timeout).then(|| Dynamic::from("time limit exceeded"))
+        });
+        engine
+    }
+
+    /// Compile a script and check it defines `select(ctx)`
+    pub fn compile(&self, source: &str, version: i32) -> Result<CompiledScript> {
+        let engine = self.engine(Arc::new(AtomicU64::new(0)));
+        let ast = engine.compile(source)
+            .map_err(|e| anyhow::anyhow!("Script does not compile: {}", e))?;
+        if !ast.iter_functions().any(|f| f.name == "select" && f.params.len() == 1) {
+            anyhow::bail!("Script must define fn select(ctx)");
+        }
+        Ok(CompiledScript { source: source.to_string(), version, ast })
+    }
+
+    /// Run a compiled script (blocking; call from `spawn_blocking`)
+    pub fn run(&self, script: &CompiledScript, ctx: &ScriptContext) -> Result<ScriptRun> {
+        let operations = Arc::new(AtomicU64::new(0));
+        let engine = self.engine(operations.clone());
+        let started = Instant::now();
+
+        let result = engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &script.ast, "select", (ctx.to_map(),))
+            .map_err(|e| anyhow::anyhow!("Script failed: {}", e))?;
+
+        Ok(ScriptRun {
+            decision: parse_decision(result, ctx)?,
+            operations: operations.load(Ordering::Relaxed),
+            elapsed_us: started.elapsed().as_micros() as u64,
+        })
+    }
+
+    /// Compile and dry-run a script against a sample grid
+    pub fn validate(&self, source: &str) -> Result<ScriptRun> {
+        let script = self.compile(source, 0)?;
+        self.run(&script, &ScriptContext::sample())
+    }
+
+    /// Install a session's script; the mining loop picks it up next round
+    pub fn set(&self, session_id: Uuid, script: CompiledScript) {
+        self.scripts.write().insert(session_id, Arc::new(script));
+    }
+
+    /// Remove a session's script
+    pub fn remove(&self, session_id: Uuid) -> bool {
+        self.scripts.write().remove(&session_id).is_some()
+    }
+
+    /// A session's current script
+    pub fn get(&self, session_id: Uuid) -> Option<Arc<CompiledScript>> {
+        self.scripts.read().get(&session_id).cloned()
+    }
+}
+
+/// Turn a script's return value into a checked decision
+fn parse_decision(result: Dynamic, ctx: &ScriptContext) -> Result<ScriptDecision> {
+    if result.is_unit() {
+        return Ok(ScriptDecision::Skip { reason: "script returned ()".to_string() });
+    }
+    let map = result.try_cast::<Map>().context("select() must return a map or ()")?;
+
+    if map.get("skip").and_then(|v| v.as_bool().ok()).unwrap_or(false) {
+        let reason = map.get("reason")
+            .and_then(|v| v.clone().into_string().ok())
+            .unwrap_or_else(|| "script skipped".to_string());
+        return Ok(ScriptDecision::Skip { reason });
+    }
+
+    let squares = map.get("squares")
+        .and_then(|v| v.clone().into_array().ok())
+        .context("select() must return `squares` (array of 0-24)")?;
+    let mut out_squares = Vec::with_capacity(squares.len());
+    for square in squares {
+        let index = square.as_int().map_err(|t| anyhow::anyhow!("square must be an integer, got {}", t))?;
+        if !(0..25).contains(&index) {
+            anyhow::bail!("square {} is out of range 0-24", index);
+        }
+        if out_squares.contains(&(index as u8)) {
+            anyhow::bail!("square {} is listed twice", index);
+        }
+        out_squares.push(index as u8);
+    }
+    if out_squares.is_empty() {
+        anyhow::bail!("`squares` is empty; return #{{ skip: true }} to skip");
+    }
+
+    let amounts = match map.get("amounts").and_then(|v| v.clone().into_array().ok()) {
+        Some(amounts) => {
+            if amounts.len() != out_squares.len() {
+                anyhow::bail!("`amounts` has {} entries for {} squares", amounts.len(), out_squares.len());
+            }
+            amounts.into_iter()
+                .map(|a| match a.as_int() {
+                    Ok(a) if a > 0 && a as u64 <= MAX_AMOUNT_PER_SQUARE => Ok(a as u64),
+                    _ => Err(anyhow::anyhow!("amounts must be 1..={} lamports", MAX_AMOUNT_PER_SQUARE)),
+                })
+                .collect::<Result<Vec<u64>>>()?
+        }
+        None => vec![ctx.deploy_amount; out_squares.len()],
+    };
+
+    Ok(ScriptDecision::Deploy { squares: out_squares, amounts })
+}
+
+/// Block selection for the mining loop, plus the amount the script asked
+/// for on each selected square. A failed run skips the round.
+pub fn to_selection(run: Result<ScriptRun>, version: i32) -> (AiSelection, Option<Vec<u64>>) {
+    let skip = |reasoning: String| AiSelection {
+        blocks: Vec::new(),
+        confidence: 1.0,
+        skip: true,
+        reasoning,
+        weights: Vec::new(),
+    };
+    match run {
+        Ok(ScriptRun { decision: ScriptDecision::Deploy { squares, amounts }, operations, elapsed_us }) => {
+            let selection = AiSelection {
+                weights: equal_weights(squares.len()),
+                blocks: squares,
+                confidence: 1.0,
+                skip: false,
+                reasoning: format!("Script v{} ({} ops, {}us)", version, operations, elapsed_us),
+            };
+            (selection, Some(amounts))
+        }
+        Ok(ScriptRun { decision: ScriptDecision::Skip { reason }, .. }) => (skip(reason), None),
+        Err(e) => (skip(format!("Script error: {}", e)), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_selects_lowest_square() {
+        let runner = ScriptRunner::new(ScriptLimits::default());
+        let run = runner.validate(
+            r#"
+            fn select(ctx) {
+                let best = ctx.blocks[0];
+                for b in ctx.blocks { if b.deployed < best.deployed { best = b; } }
+                #{ squares: [best.index], amounts: [ctx.deploy_amount * 2] }
+            }
+            "#,
+        ).unwrap();
+        assert_eq!(run.decision, ScriptDecision::Deploy { squares: vec![0], amounts: vec![2_000_000] });
+
+        let skip = runner.validate(r#"fn select(ctx) { #{ skip: true, reason: "flat" } }"#).unwrap();
+        assert_eq!(skip.decision, ScriptDecision::Skip { reason: "flat".to_string() });
+    }
+
+    #[test]
+    fn test_script_limits_and_validation() {
+        let runner = ScriptRunner::new(ScriptLimits { max_operations: 10_000, timeout: Duration::from_millis(50) });
+        assert!(runner.validate("fn select(ctx) { loop { } }").is_err());
+        assert!(runner.validate("fn pick(ctx) { () }").is_err());
+        assert!(runner.validate("fn select(ctx) { #{ squares: [3, 3] } }").is_err());
+        assert!(runner.validate("fn select(ctx) { #{ squares: [25] } }").is_err());
+        assert!(runner.validate("fn select(ctx) { #{ squares: [1, 2], amounts: [1000] } }").is_err());
+        let split = runner.validate("fn select(ctx) { #{ squares: [1, 2], amounts: [1000, 2000] } }").unwrap();
+        assert_eq!(to_selection(Ok(split), 1).1, Some(vec![1000, 2000]));
+
+        let (selection, amount) = to_selection(runner.validate("fn select(ctx) { #{ squares: [\"a\"] } }"), 1);
+        assert!(selection.skip && selection.reasoning.starts_with("Script error"));
+        assert_eq!(amount, None);
+    }
+}
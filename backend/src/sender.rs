@@ -0,0 +1,445 @@
+//! Transaction Submission Paths
+//!
+//! Sends the same signed transaction through several paths at once: extra
+//! RPC endpoints, a Jito bundle, and direct TPU/QUIC to the current and
+//! upcoming leaders. Per-path acceptance, latency and landings are recorded
+//! so each path's value can be judged from data.
+//!
+//! Every path carries the same deploy signature, but only the Jito bundle
+//! carries the tip transaction: a landed tip means Jito delivered the deploy.
+//! Other landings are credited to the first RPC or TPU path that accepted.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Result, Context};
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use solana_client::nonblocking::tpu_client::TpuClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::tpu_client::TpuClientConfig;
+use solana_quic_client::{QuicConfig, QuicConnectionManager, QuicPool};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature, transaction::Transaction};
+use tracing::{debug, info, warn};
+
+use crate::jito::{BundleStatus, JitoClient};
+use crate::ore::OreClient;
+
+type QuicTpuClient = TpuClient<QuicPool, QuicConnectionManager, QuicConfig>;
+
+/// A path a transaction can be submitted through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathKind {
+    Rpc,
+    Jito,
+    Tpu,
+}
+
+impl FromStr for PathKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "rpc" => Ok(Self::Rpc),
+            "jito" => Ok(Self::Jito),
+            "tpu" | "quic" => Ok(Self::Tpu),
+            other => anyhow::bail!("Unknown send path: {}", other),
+        }
+    }
+}
+
+/// Which paths a deploy is raced through
+#[derive(Debug, Clone)]
+pub struct SubmissionPolicy {
+    pub paths: Vec<PathKind>,
+    /// Extra RPC endpoints used alongside the primary RPC
+    pub extra_rpc_urls: Vec<String>,
+    /// Websocket URL for leader schedule tracking (TPU path)
+    pub websocket_url: Option<String>,
+    /// How many upcoming leaders receive the transaction over TPU
+    pub tpu_fanout_slots: u64,
+}
+
+impl Default for SubmissionPolicy {
+    fn default() -> Self {
+        Self {
+            paths: vec![PathKind::Rpc],
+            extra_rpc_urls: Vec::new(),
+            websocket_url: None,
+            tpu_fanout_slots: 2,
+        }
+    }
+}
+
+impl SubmissionPolicy {
+    pub fn uses(&self, kind: PathKind) -> bool {
+        self.paths.contains(&kind)
+    }
+}
+
+/// Outcome of one path's send attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct PathAttempt {
+    pub path: String,
+    pub kind: PathKind,
+    pub accepted: bool,
+    pub ack_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Result of racing a transaction through every configured path
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceReport {
+    pub signature: String,
+    /// Path that accepted the transaction first
+    pub first_path: Option<String>,
+    /// Attempts finished by the time the first path accepted
+    pub attempts: Vec<PathAttempt>,
+    /// Signature of the Jito bundle's tip transaction
+    pub tip_signature: Option<String>,
+    /// Attempts still running when `race` returned, added as they finish
+    #[serde(skip)]
+    late_attempts: Arc<Mutex<Vec<PathAttempt>>>,
+}
+
+impl RaceReport {
+    /// Report for a transaction that wasn't sent (it needs frontend signing)
+    pub fn unsent(signature: String) -> Self {
+        Self {
+            signature,
+            first_path: None,
+            attempts: Vec::new(),
+            tip_signature: None,
+            late_attempts: Arc::default(),
+        }
+    }
+}
+
+/// Counters for one submission path
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PathStats {
+    pub sent: u64,
+    pub accepted: u64,
+    /// Times this path accepted before every other path
+    pub first_accepts: u64,
+    /// Landings this path delivered
+    pub landed_first: u64,
+    pub avg_ack_ms: f64,
+}
+
+/// Races signed transactions through the configured submission paths
+#[derive(Clone)]
+pub struct TxSender {
+    ore_client: OreClient,
+    jito_client: JitoClient,
+    policy: SubmissionPolicy,
+    extra_rpcs: Arc<Vec<(String, Arc<AsyncRpcClient>)>>,
+    tpu_client: Option<Arc<QuicTpuClient>>,
+    stats: Arc<RwLock<HashMap<String, PathStats>>>,
+}
+
+/// Short label for an RPC URL (host only, so API keys don't leak into stats)
+fn rpc_label(url: &str) -> String {
+    let host = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split(['/', '?'])
+        .next()
+        .unwrap_or(url);
+    format!("rpc:{}", host)
+}
+
+impl TxSender {
+    /// Create a sender for a submission policy
+    ///
+    /// The TPU path needs a websocket for leader tracking; if it can't be set
+    /// up the path is dropped with a warning rather than failing startup.
+    pub async fn new(
+        ore_client: OreClient,
+        jito_client: JitoClient,
+        rpc_url: &str,
+        mut policy: SubmissionPolicy,
+    ) -> Self {
+        let extra_rpcs = policy.extra_rpc_urls.iter()
+            .filter(|url| url.as_str() != rpc_url)
+            .map(|url| {
+                let client = AsyncRpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed());
+                (rpc_label(url), Arc::new(client))
+            })
+            .collect();
+
+        let tpu_client = if policy.uses(PathKind::Tpu) {
+            match Self::connect_tpu(rpc_url, &policy).await {
+                Ok(client) => Some(Arc::new(client)),
+                Err(e) => {
+                    warn!("TPU path disabled: {}", e);
+                    policy.paths.retain(|p| *p != PathKind::Tpu);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        info!("Submission paths: {:?} (+{} extra RPCs)", policy.paths, policy.extra_rpc_urls.len());
+
+        Self {
+            ore_client,
+            jito_client,
+            policy,
+            extra_rpcs: Arc::new(extra_rpcs),
+            tpu_client,
+            stats: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// A sender that only uses the primary RPC
+    pub fn rpc_only(ore_client: OreClient, jito_client: JitoClient) -> Self {
+        Self {
+            ore_client,
+            jito_client,
+            policy: SubmissionPolicy::default(),
+            extra_rpcs: Arc::new(Vec::new()),
+            tpu_client: None,
+            stats: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    async fn connect_tpu(rpc_url: &str, policy: &SubmissionPolicy) -> Result<QuicTpuClient> {
+        let ws_url = policy.websocket_url.clone()
+            .unwrap_or_else(|| rpc_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1));
+        let rpc = Arc::new(AsyncRpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()));
+
+        TpuClient::new(
+            "orevault",
+            rpc,
+            &ws_url,
+            TpuClientConfig { fanout_slots: policy.tpu_fanout_slots },
+        )
+        .await
+        .context("Failed to start TPU client")
+    }
+
+    /// The active submission policy
+    pub fn policy(&self) -> &SubmissionPolicy {
+        &self.policy
+    }
+
+    /// Send a signed transaction through every configured path at once
+    ///
+    /// `tip_tx` is bundled after `tx` on the Jito path; other paths only
+    /// carry `tx` itself. Returns as soon as one path accepts; the other
+    /// paths finish in the background.
+    pub async fn race(&self, tx: &Transaction, tip_tx: Option<&Transaction>) -> Result<RaceReport> {
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+        if signature == Signature::default() {
+            anyhow::bail!("Transaction is not signed");
+        }
+
+        let mut sends: FuturesUnordered<BoxFuture<'static, PathAttempt>> = FuturesUnordered::new();
+
+        if self.policy.uses(PathKind::Rpc) {
+            let (ore_client, tx) = (self.ore_client.clone(), tx.clone());
+            sends.push(Box::pin(Self::timed("rpc:primary".to_string(), PathKind::Rpc, async move {
+                ore_client.send_transaction(&tx).await.map(|_| ())
+            })));
+            for (label, rpc) in self.extra_rpcs.iter() {
+                let (rpc, tx) = (rpc.clone(), tx.clone());
+                sends.push(Box::pin(Self::timed(label.clone(), PathKind::Rpc, async move {
+                    let config = RpcSendTransactionConfig {
+                        skip_preflight: true,
+                        ..Default::default()
+                    };
+                    rpc.send_transaction_with_config(&tx, config).await
+                        .map(|_| ())
+                        .map_err(anyhow::Error::from)
+                })));
+            }
+        }
+
+        if self.policy.uses(PathKind::Jito) {
+            let jito_client = self.jito_client.clone();
+            let mut bundle = vec![tx.clone()];
+            bundle.extend(tip_tx.cloned());
+            sends.push(Box::pin(Self::timed("jito".to_string(), PathKind::Jito, async move {
+                let result = jito_client.send_bundle(bundle).await?;
+                match result.status {
+                    BundleStatus::Failed { reason } => Err(anyhow::anyhow!(reason)),
+                    _ => Ok(()),
+                }
+            })));
+        }
+
+        if let Some(tpu) = &self.tpu_client {
+            let (tpu, tx) = (tpu.clone(), tx.clone());
+            sends.push(Box::pin(Self::timed("tpu".to_string(), PathKind::Tpu, async move {
+                tpu.try_send_transaction(&tx).await.map_err(anyhow::Error::from)
+            })));
+        }
+
+        // Attempts finish in order, so the first accepted one is the fastest
+        let mut attempts = Vec::new();
+        let mut first_path = None;
+        while let Some(attempt) = sends.next().await {
+            if attempt.accepted {
+                first_path = Some(attempt.path.clone());
+            }
+            attempts.push(attempt);
+            if first_path.is_some() {
+                break;
+            }
+        }
+
+        self.record_attempts(&attempts, first_path.as_deref());
+
+        if first_path.is_none() {
+            let reasons: Vec<String> = attempts.iter()
+                .map(|a| format!("{}: {}", a.path, a.error.as_deref().unwrap_or("rejected")))
+                .collect();
+            anyhow::bail!("All submission paths failed: {}", reasons.join("; "));
+        }
+
+        let late_attempts: Arc<Mutex<Vec<PathAttempt>>> = Arc::default();
+        if !sends.is_empty() {
+            let (sender, late) = (self.clone(), late_attempts.clone());
+            tokio::spawn(async move {
+                while let Some(attempt) = sends.next().await {
+                    sender.record_attempts(std::slice::from_ref(&attempt), None);
+                    late.lock().push(attempt);
+                }
+            });
+        }
+
+        info!("Transaction {} accepted first by {:?}", signature, first_path);
+        Ok(RaceReport {
+            signature: signature.to_string(),
+            first_path,
+            attempts,
+            tip_signature: tip_tx
+                .filter(|_| self.policy.uses(PathKind::Jito))
+                .and_then(|t| t.signatures.first())
+                .map(|s| s.to_string()),
+            late_attempts,
+        })
+    }
+
+    /// Resend an already-raced transaction over the RPC and TPU paths
+    ///
+    /// Jito is left out: a resent bundle would carry the same tip transaction
+    /// and only adds load on the block engines. Returns how many paths accepted.
+    pub async fn resend(&self, tx: &Transaction) -> usize {
+        let mut sends: Vec<BoxFuture<'_, bool>> = Vec::new();
+
+        if self.policy.uses(PathKind::Rpc) {
+            sends.push(Box::pin(async { self.ore_client.send_transaction(tx).await.is_ok() }));
+            for (_, rpc) in self.extra_rpcs.iter() {
+                sends.push(Box::pin(async move {
+                    let config = RpcSendTransactionConfig {
+                        skip_preflight: true,
+                        ..Default::default()
+                    };
+                    rpc.send_transaction_with_config(tx, config).await.is_ok()
+                }));
+            }
+        }
+        if let Some(tpu) = &self.tpu_client {
+            sends.push(Box::pin(async move { tpu.try_send_transaction(tx).await.is_ok() }));
+        }
+
+        futures::future::join_all(sends).await.into_iter().filter(|ok| *ok).count()
+    }
+
+    /// Credit a landed transaction to the path that delivered it: Jito if
+    /// the bundle's tip transaction landed, else the first RPC or TPU path
+    /// that accepted it
+    pub async fn record_landing(&self, report: &RaceReport) {
+        let tip_landed = match report.tip_signature.as_deref().and_then(|s| s.parse::<Signature>().ok()) {
+            Some(tip) => matches!(self.ore_client.rpc().get_signature_status(&tip).await, Ok(Some(Ok(())))),
+            None => false,
+        };
+        let path = if tip_landed {
+            Some("jito".to_string())
+        } else {
+            let late = report.late_attempts.lock().clone();
+            report.attempts.iter()
+                .chain(late.iter())
+                .filter(|a| a.accepted && a.kind != PathKind::Jito)
+                .min_by_key(|a| a.ack_ms)
+                .map(|a| a.path.clone())
+        };
+        if let Some(path) = path {
+            debug!("Landing of {} credited to {}", report.signature, path);
+            self.stats.write().entry(path).or_default().landed_first += 1;
+        }
+    }
+
+    /// Per-path counters
+    pub fn path_stats(&self) -> HashMap<String, PathStats> {
+        self.stats.read().clone()
+    }
+
+    fn record_attempts(&self, attempts: &[PathAttempt], first_path: Option<&str>) {
+        let mut stats = self.stats.write();
+        for attempt in attempts {
+            let entry = stats.entry(attempt.path.clone()).or_default();
+            entry.sent += 1;
+            if attempt.accepted {
+                entry.avg_ack_ms = if entry.accepted == 0 {
+                    attempt.ack_ms as f64
+                } else {
+                    entry.avg_ack_ms * 0.8 + attempt.ack_ms as f64 * 0.2
+                };
+                entry.accepted += 1;
+            }
+            if first_path == Some(attempt.path.as_str()) {
+                entry.first_accepts += 1;
+            }
+        }
+    }
+
+    async fn timed<F>(path: String, kind: PathKind, send: F) -> PathAttempt
+    where
+        F: std::future::Future<Output = Result<()>>,
+    {
+        let started = Instant::now();
+        let result = send.await;
+        let ack_ms = started.elapsed().as_millis() as u64;
+        if let Err(e) = &result {
+            debug!("Send path {} failed after {}ms: {}", path, ack_ms, e);
+        }
+        PathAttempt {
+            path,
+            kind,
+            accepted: result.is_ok(),
+            ack_ms,
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_kind_parse() {
+        assert_eq!("rpc".parse::<PathKind>().unwrap(), PathKind::Rpc);
+        assert_eq!("JITO".parse::<PathKind>().unwrap(), PathKind::Jito);
+        assert_eq!("quic".parse::<PathKind>().unwrap(), PathKind::Tpu);
+        assert!("carrier-pigeon".parse::<PathKind>().is_err());
+    }
+
+    #[test]
+    fn test_rpc_label_hides_api_key() {
+        assert_eq!(
+            rpc_label("https://mainnet.helius-rpc.com/?api-key=secret"),
+            "rpc:mainnet.helius-rpc.com"
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Pre-submission Simulation
+//!
+//! Simulates deploy transactions before they are sent. `send_transaction`
+//! skips preflight, so a stale round_id, empty automation account or missing
+//! checkpoint would otherwise just burn fees on-chain.
+//! Uses `simulateBundle` on the block engine or `simulateTransaction` on RPC.
+
+use std::str::FromStr;
+
+use serde::Serialize;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use tokio::time::{timeout, Duration};
+use tracing::{debug, info, warn};
+
+use crate::jito::JitoClient;
+use crate::ore::{OreClient, OreProgramError};
+
+/// Where deploy transactions are simulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationMode {
+    Off,
+    /// `simulateTransaction` on the RPC node
+    #[default]
+    Rpc,
+    /// `simulateBundle` on the Jito block engine
+    Bundle,
+}
+
+impl FromStr for SimulationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "none" | "0" => Ok(Self::Off),
+            "rpc" => Ok(Self::Rpc),
+            "bundle" | "jito" => Ok(Self::Bundle),
+            other => anyhow::bail!("Unknown simulation mode: {}", other),
+        }
+    }
+}
+
+/// Simulation settings
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub mode: SimulationMode,
+    /// Skip simulation when fewer slots than this remain in the round
+    pub min_slots: u64,
+    /// Give up on simulation (and submit anyway) after this long
+    pub timeout: Duration,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            mode: SimulationMode::Rpc,
+            min_slots: 3,
+            timeout: Duration::from_millis(400),
+        }
+    }
+}
+
+/// Typed simulation failure
+#[derive(Debug, Clone, thiserror::Error, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimulationError {
+    #[error("ORE program error in instruction {index}: {error}")]
+    Ore { index: u8, error: OreProgramError },
+    #[error("instruction {index} failed: {message}")]
+    Instruction { index: u8, message: String },
+    #[error("transaction failed: {message}")]
+    Transaction { message: String },
+}
+
+impl SimulationError {
+    /// Decode a transaction error, naming ORE program errors where possible
+    pub fn from_transaction_error(tx: &Transaction, err: &TransactionError) -> Self {
+        match err {
+            TransactionError::InstructionError(index, ix_err) => {
+                match OreProgramError::from_transaction_error(tx, err) {
+                    Some(error) => Self::Ore { index: *index, error },
+                    None => Self::Instruction { index: *index, message: format!("{:?}", ix_err) },
+                }
+            }
+            other => Self::Transaction { message: format!("{:?}", other) },
+        }
+    }
+}
+
+/// Result of simulating a deploy
+#[derive(Debug, Clone)]
+pub enum SimulationOutcome {
+    Passed { units_consumed: Option<u64> },
+    /// Not simulated (disabled, too late in the round, or simulator unavailable)
+    Skipped { reason: String },
+    Failed { error: SimulationError, logs: Vec<String> },
+}
+
+impl SimulationOutcome {
+    /// Whether the transaction should be submitted
+    pub fn should_submit(&self) -> bool {
+        !matches!(self, SimulationOutcome::Failed { .. })
+    }
+}
+
+/// Runs deploy simulations within the round's time budget
+#[derive(Clone)]
+pub struct Simulator {
+    ore_client: OreClient,
+    jito_client: JitoClient,
+    config: SimulationConfig,
+}
+
+impl Simulator {
+    /// Create a new simulator
+    pub fn new(ore_client: OreClient, jito_client: JitoClient, config: SimulationConfig) -> Self {
+        Self { ore_client, jito_client, config }
+    }
+
+    /// Simulate a signed transaction, skipping when too few slots remain
+    pub async fn simulate(&self, tx: &Transaction, slots_remaining: u64) -> SimulationOutcome {
+        if slots_remaining < self.config.min_slots {
+            return SimulationOutcome::Skipped {
+                reason: format!("only {} slots remaining", slots_remaining),
+            };
+        }
+
+        let started = std::time::Instant::now();
+        let result = match self.config.mode {
+            SimulationMode::Rpc => timeout(self.config.timeout, self.simulate_rpc(tx)).await,
+            SimulationMode::Bundle => timeout(self.config.timeout, self.simulate_bundle(tx)).await,
+            SimulationMode::Off => return SimulationOutcome::Skipped { reason: "simulation disabled".to_string() },
+        };
+
+        let outcome = match result {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(e)) => {
+                // Simulator trouble shouldn't block a deploy - the RPC path used to skip preflight entirely
+                warn!("Simulation unavailable, submitting without it: {}", e);
+                SimulationOutcome::Skipped { reason: format!("simulator error: {}", e) }
+            }
+            Err(_) => SimulationOutcome::Skipped {
+                reason: format!("simulation exceeded {}ms budget", self.config.timeout.as_millis()),
+            },
+        };
+
+        match &outcome {
+            SimulationOutcome::Passed { units_consumed } => {
+                debug!("Simulation passed in {}ms ({:?} CU)", started.elapsed().as_millis(), units_consumed);
+            }
+            SimulationOutcome::Skipped { reason } => info!("Simulation skipped: {}", reason),
+            SimulationOutcome::Failed { error, logs } => {
+                warn!("Simulation failed: {}\nLogs:\n{}", error, logs.join("\n"));
+            }
+        }
+
+        outcome
+    }
+
+    /// Simulate via `simulateTransaction`
+    async fn simulate_rpc(&self, tx: &Transaction) -> anyhow::Result<SimulationOutcome> {
+        use solana_client::rpc_config::RpcSimulateTransactionConfig;
+
+        let sim_config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: false,
+            commitment: Some(solana_sdk::commitment_config::CommitmentConfig::processed()),
+            ..Default::default()
+        };
+
+        let result = self.ore_client.rpc()
+            .simulate_transaction_with_config(tx, sim_config)
+            .await?
+            .value;
+
+        let logs = result.logs.unwrap_or_default();
+        Ok(match result.err {
+            Some(err) => SimulationOutcome::Failed {
+                error: SimulationError::from_transaction_error(tx, &err),
+                logs,
+            },
+            None => SimulationOutcome::Passed { units_consumed: result.units_consumed },
+        })
+    }
+
+    /// Simulate via `simulateBundle`
+    async fn simulate_bundle(&self, tx: &Transaction) -> anyhow::Result<SimulationOutcome> {
+        let sim = self.jito_client.simulate_bundle(std::slice::from_ref(tx)).await?;
+
+        Ok(match (sim.failed_tx, sim.error) {
+            (None, _) => SimulationOutcome::Passed { units_consumed: sim.units_consumed },
+            (Some(_), Some(err)) => SimulationOutcome::Failed {
+                error: SimulationError::from_transaction_error(tx, &err),
+                logs: sim.logs,
+            },
+            (Some(_), None) => SimulationOutcome::Failed {
+                error: SimulationError::Transaction {
+                    message: sim.raw_error.unwrap_or_else(|| "unknown bundle failure".to_string()),
+                },
+                logs: sim.logs,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation_mode_parse() {
+        assert_eq!("rpc".parse::<SimulationMode>().unwrap(), SimulationMode::Rpc);
+        assert_eq!("Bundle".parse::<SimulationMode>().unwrap(), SimulationMode::Bundle);
+        assert_eq!("off".parse::<SimulationMode>().unwrap(), SimulationMode::Off);
+        assert!("sometimes".parse::<SimulationMode>().is_err());
+    }
+
+    #[test]
+    fn test_only_failures_block_submission() {
+        assert!(SimulationOutcome::Passed { units_consumed: None }.should_submit());
+        assert!(SimulationOutcome::Skipped { reason: "late".into() }.should_submit());
+        assert!(!SimulationOutcome::Failed {
+            error: SimulationError::Transaction { message: "BlockhashNotFound".into() },
+            logs: vec![],
+        }.should_submit());
+    }
+}
@@ -0,0 +1,232 @@
+//! Stake Sizing
+//!
+//! Per-round deploy size. `Fixed` keeps the session's deploy amount; `Kelly`
+//! stakes a fraction of the Kelly-optimal share of the bankroll (wallet plus
+//! automation balance), using the edge the EV model gives for the selected
+//! squares, tip included. Since a bigger stake dilutes its own square, the
+//! size is refined a few times at the edge of the previous estimate. The
+//! result is clamped to the per-square bounds and the session's remaining
+//! budget; without an edge Kelly stakes nothing and the round is sat out.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::ev::EvParams;
+
+/// Refinement passes for Kelly sizing
+const KELLY_PASSES: usize = 3;
+
+/// How a session sizes each round's deploy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SizingPolicy {
+    /// The session's deploy amount every round
+    #[default]
+    Fixed,
+    /// Fractional Kelly on the estimated edge
+    Kelly {
+        /// Multiplier on the full-Kelly stake (e.g. 0.25 for quarter Kelly)
+        fraction: f64,
+        /// SOL per square
+        min_per_square: f64,
+        /// SOL per square
+        max_per_square: f64,
+    },
+}
+
+/// Round inputs for sizing
+pub struct SizingInput<'a> {
+    pub params: &'a EvParams,
+    pub deployed: &'a [u64],
+    pub motherlode: u64,
+    pub squares: &'a [u8],
+    /// Session deploy amount (lamports per square)
+    pub base_amount: u64,
+    pub tip: u64,
+    /// Wallet plus automation balance (lamports)
+    pub bankroll: u64,
+    /// Session budget left (lamports), if known
+    pub remaining_budget: Option<u64>,
+}
+
+/// The size chosen for a round, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StakeSize {
+    /// Lamports per square (0 = don't deploy)
+    pub per_square: u64,
+    /// Expected return per lamport spent (deploys and tip), minus one
+    pub edge: Option<f64>,
+    /// Full-Kelly share of the bankroll
+    pub kelly: Option<f64>,
+    pub reason: String,
+}
+
+fn lamports(sol: f64) -> u64 {
+    (sol * 1_000_000_000.0) as u64
+}
+
+fn sol(lamports: u64) -> f64 {
+    lamports as f64 / 1_000_000_000.0
+}
+
+impl SizingPolicy {
+    /// Check the policy's bounds
+    pub fn validate(&self) -> Result<()> {
+        if let Self::Kelly { fraction, min_per_square, max_per_square } = *self {
+            if !fraction.is_finite() || fraction <= 0.0 || fraction > 1.0 {
+                anyhow::bail!("Kelly fraction must be > 0 and <= 1");
+            }
+            if !min_per_square.is_finite() || min_per_square < 0.0001 || !max_per_square.is_finite() || max_per_square > 10.0 {
+                anyhow::bail!("Kelly bounds must be >= 0.0001 and <= 10 (SOL per square)");
+            }
+            if min_per_square > max_per_square {
+                anyhow::bail!("Kelly min_per_square must not exceed max_per_square");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the policy needs the bankroll
+    pub fn needs_bankroll(&self) -> bool {
+        matches!(self, Self::Kelly { .. })
+    }
+
+    /// Lamports per square for this round
+    pub fn size(&self, input: &SizingInput) -> StakeSize {
+        let squares = input.squares.len().max(1) as u64;
+        let budget_cap = input.remaining_budget.map(|b| b.saturating_sub(input.tip) / squares);
+
+        let (fraction, min, max) = match *self {
+            Self::Fixed => {
+                return match budget_cap {
+                    Some(cap) if cap < input.base_amount => StakeSize {
+                        per_square: 0,
+                        edge: None,
+                        kelly: None,
+                        reason: format!("Budget left ({:.4} SOL) is below the fixed size", sol(input.remaining_budget.unwrap_or(0))),
+                    },
+                    _ => StakeSize {
+                        per_square: input.base_amount,
+                        edge: None,
+                        kelly: None,
+                        reason: "Fixed session size".to_string(),
+                    },
+                };
+            }
+            Self::Kelly { fraction, min_per_square, max_per_square } => {
+                (fraction, lamports(min_per_square), lamports(max_per_square))
+            }
+        };
+
+        let mut per_square = input.base_amount.clamp(min, max);
+        let mut edge = 0.0;
+        let mut kelly = 0.0;
+        for _ in 0..KELLY_PASSES {
+            let ev = input.params.uniform(input.deployed, input.motherlode, input.squares, per_square, input.tip);
+            let claimed = ev.expected_sol + input.params.ore_value(ev.expected_ore);
+            edge = claimed as f64 / ev.cost.max(1) as f64 - 1.0;
+            if edge <= 0.0 || ev.win_probability <= 0.0 {
+                kelly = 0.0;
+                break;
+            }
+            // Binary bet: win with p, net odds b; full Kelly = (p(b + 1) - 1) / b
+            let p = ev.win_probability;
+            let b = (1.0 + edge) / p - 1.0;
+            kelly = edge / b;
+            // The tip comes out of the stake; the rest is split over the squares
+            let stake = (fraction * kelly * input.bankroll as f64) as u64;
+            per_square = (stake.saturating_sub(input.tip) / squares).clamp(min, max);
+        }
+
+        if kelly <= 0.0 {
+            return StakeSize {
+                per_square: 0,
+                edge: Some(edge),
+                kelly: Some(0.0),
+                reason: format!("No edge ({:+.2}%) after the tip", edge * 100.0),
+            };
+        }
+        let mut reason = format!(
+            "{:.2}x Kelly: {:.3}% of {:.4} SOL bankroll at {:+.2}% edge",
+            fraction, kelly * 100.0, sol(input.bankroll), edge * 100.0
+        );
+        if per_square == max {
+            reason.push_str(", capped at max per square");
+        } else if per_square == min {
+            reason.push_str(", raised to min per square");
+        }
+        if let Some(cap) = budget_cap.filter(|&cap| cap < per_square) {
+            per_square = if cap >= min { cap } else { 0 };
+            reason.push_str(&format!(", limited by budget left ({:.4} SOL)", sol(input.remaining_budget.unwrap_or(0))));
+        }
+
+        StakeSize {
+            per_square,
+            edge: Some(edge),
+            kelly: Some(kelly),
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn kelly() -> SizingPolicy {
+        SizingPolicy::Kelly { fraction: 0.25, min_per_square: 0.001, max_per_square: 1.0 }
+    }
+
+    fn input<'a>(params: &'a EvParams, deployed: &'a [u64], squares: &'a [u8], budget: Option<u64>) -> SizingInput<'a> {
+        SizingInput {
+            params,
+            deployed,
+            motherlode: 0,
+            squares,
+            base_amount: SOL / 100,
+            tip: 0,
+            bankroll: 10 * SOL,
+            remaining_budget: budget,
+        }
+    }
+
+    #[test]
+    fn test_kelly_sizes_an_edge() {
+        let params = EvParams::default();
+        // Empty square 0 while the rest hold 1 SOL each: a large edge
+        let mut deployed = vec![SOL; 25];
+        deployed[0] = 0;
+        let size = kelly().size(&input(&params, &deployed, &[0], None));
+        assert!(size.edge.unwrap() > 0.0 && size.kelly.unwrap() > 0.0);
+        assert!(size.per_square > SOL / 1000 && size.per_square <= SOL, "{:?}", size);
+
+        // Budget caps the size
+        let capped = kelly().size(&input(&params, &deployed, &[0], Some(SOL / 500)));
+        assert_eq!(capped.per_square, SOL / 500);
+        assert!(capped.reason.contains("budget"));
+    }
+
+    #[test]
+    fn test_kelly_without_edge_sits_out() {
+        let params = EvParams::default();
+        let mut deployed = vec![SOL; 25];
+        deployed[0] = 100 * SOL;
+        let size = kelly().size(&input(&params, &deployed, &[0], None));
+        assert_eq!(size.per_square, 0);
+        assert!(size.reason.starts_with("No edge"));
+
+        // A square with an edge before the tip has none once a large tip is counted
+        deployed[0] = 0;
+        let squares = [0];
+        let tipped = SizingInput { tip: 10 * SOL, ..input(&params, &deployed, &squares, None) };
+        let size = kelly().size(&tipped);
+        assert!(size.edge.unwrap() <= 0.0, "{:?}", size);
+        assert_eq!(size.per_square, 0);
+
+        let fixed = SizingPolicy::Fixed.size(&input(&params, &deployed, &[0], Some(SOL / 1000)));
+        assert_eq!(fixed.per_square, 0);
+        assert!(SizingPolicy::Kelly { fraction: 2.0, min_per_square: 0.001, max_per_square: 1.0 }.validate().is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! WebSocket Module
 //! 
 //! Handles real-time communication between frontend and backend.
-//! Events: round:update, decision:made, tx:confirmed, balance:update, claim:confirmed
+//! Events: round:update, decision:made, tx:confirmed, tx:failed, balance:update, claim:confirmed
 
 use std::sync::Arc;
 use std::collections::HashMap;
@@ -32,9 +32,14 @@ pub enum WsEvent {
     #[serde(rename = "ai:analysis")]
     AiAnalysis {
         selected_block: u8,
+        blocks: Vec<u8>,
+        weights: Vec<f64>,
         confidence: f64,
         reasoning: String,
         skip: bool,
+        source: String,
+        latency_ms: Option<u64>,
+        margin_ms: i64,
     },
     
     /// Decision made (deploy or skip)
@@ -44,6 +49,12 @@ pub enum WsEvent {
         block: Option<u8>,
         ev: f64,
         reason: Option<String>,
+        /// SOL per square chosen by the session's sizing policy
+        per_square: Option<f64>,
+        /// Why the sizing policy chose that size
+        size_reason: Option<String>,
+        /// Verdict of every session rule
+        rules: Vec<crate::rules::RuleVerdict>,
     },
     
     /// Transaction submitted
@@ -52,6 +63,7 @@ pub enum WsEvent {
         signature: String,
         block: u8,
         amount: f64,
+        path: Option<String>,
     },
     
     /// Transaction confirmed
@@ -60,6 +72,17 @@ pub enum WsEvent {
         signature: String,
         status: String,
         reward: Option<f64>,
+        resends: u32,
+    },
+    
+    /// Transaction failed, with its diagnosis
+    #[serde(rename = "tx:failed")]
+    TxFailed {
+        signature: String,
+        cause: String,
+        remediation: String,
+        retrying: bool,
+        diagnosis: serde_json::Value,
     },
     
     /// Balance update
@@ -98,6 +121,8 @@ pub struct BlockInfo {
     pub index: u8,
     pub total_deployed: f64,
     pub ev: f64,
+    /// ORE claimed if this block wins (emission plus motherlode odds)
+    pub ore_reward: f64,
 }
 
 /// Client message types
@@ -218,7 +243,7 @@ pub async fn handle_socket(
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(100);
     
     // Subscribe to strategy events
-    let mut event_rx = state.strategy_engine.read().await.subscribe();
+    let mut event_rx = state.strategy_engine.subscribe();
     
     // Spawn task to forward strategy events via channel
     let wallet_clone = wallet.clone();
@@ -232,6 +257,7 @@ pub async fn handle_socket(
                 StrategyEvent::DecisionMade { wallet, .. } => wallet,
                 StrategyEvent::TxSubmitted { wallet, .. } => wallet,
                 StrategyEvent::TxConfirmed { wallet, .. } => wallet,
+                StrategyEvent::TxFailed { wallet, .. } => wallet,
             };
             
             if target_wallet == &wallet_clone {
@@ -325,18 +351,26 @@ fn convert_strategy_event(event: StrategyEvent) -> WsEvent {
                     index: b.index,
                     total_deployed: b.total_deployed as f64 / 1_000_000_000.0,
                     ev: b.ev / 1_000_000_000.0,
+                    ore_reward: b.ore_reward as f64 / 100_000_000_000.0,
                 }).collect(),
             }
         }
-        StrategyEvent::AiAnalysis { selected_block, confidence, reasoning, skip, .. } => {
+        StrategyEvent::AiAnalysis { selected_block, blocks, weights, confidence, reasoning, skip, source, latency_ms, margin_ms, .. } => {
             WsEvent::AiAnalysis {
                 selected_block,
+                blocks,
+                weights,
                 confidence,
                 reasoning,
                 skip,
+                source: source.as_str().to_string(),
+                latency_ms,
+                margin_ms,
             }
         }
-        StrategyEvent::DecisionMade { decision, .. } => {
+        StrategyEvent::DecisionMade { decision, sizing, rules, .. } => {
+            let per_square = sizing.as_ref().map(|s| s.per_square as f64 / 1_000_000_000.0);
+            let size_reason = sizing.map(|s| s.reason);
             match decision {
                 crate::strategy::RoundDecision::Deploy { block_index, expected_ev, .. } => {
                     WsEvent::DecisionMade {
@@ -344,6 +378,9 @@ fn convert_strategy_event(event: StrategyEvent) -> WsEvent {
                         block: Some(block_index),
                         ev: expected_ev / 1_000_000_000.0,
                         reason: None,
+                        per_square,
+                        size_reason,
+                        rules,
                     }
                 }
                 crate::strategy::RoundDecision::Skip { reason, best_ev } => {
@@ -352,22 +389,39 @@ fn convert_strategy_event(event: StrategyEvent) -> WsEvent {
                         block: None,
                         ev: best_ev / 1_000_000_000.0,
                         reason: Some(reason),
+                        per_square,
+                        size_reason,
+                        rules,
                     }
                 }
             }
         }
-        StrategyEvent::TxSubmitted { signature, block_index, amount, .. } => {
+        StrategyEvent::TxSubmitted { signature, block_index, amount, path, .. } => {
             WsEvent::TxSubmitted {
                 signature,
                 block: block_index,
                 amount: amount as f64 / 1_000_000_000.0,
+                path,
             }
         }
-        StrategyEvent::TxConfirmed { signature, status, reward, .. } => {
+        StrategyEvent::TxConfirmed { signature, status, reward, resends, .. } => {
             WsEvent::TxConfirmed {
                 signature,
                 status,
                 reward: reward.map(|r| r as f64 / 1_000_000_000.0),
+                resends,
+            }
+        }
+        StrategyEvent::TxFailed { signature, diagnosis, retrying, .. } => {
+            WsEvent::TxFailed {
+                signature,
+                cause: diagnosis.cause.label().to_string(),
+                remediation: serde_json::to_value(diagnosis.remediation)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                retrying,
+                diagnosis: serde_json::to_value(&diagnosis).unwrap_or_default(),
             }
         }
     }
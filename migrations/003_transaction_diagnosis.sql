@@ -0,0 +1,9 @@
+-- Migration 003: Failed transaction diagnosis
+-- Deploys that fail on-chain or expire carry the classified cause and the
+-- full diagnosis (decoded ORE error, logs, remediation).
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS failure_cause VARCHAR(48);
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS diagnosis JSONB;
+
+CREATE INDEX IF NOT EXISTS idx_transactions_failure_cause ON transactions(failure_cause)
+    WHERE failure_cause IS NOT NULL;
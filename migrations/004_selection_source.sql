@@ -0,0 +1,7 @@
+-- Migration 004: Block selection source
+-- Which answer picked a deploy's blocks (fresh AI call, speculative AI call
+-- or deterministic fallback) and how close to the deadline it arrived.
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS selection_source VARCHAR(16);
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS selection_latency_ms BIGINT;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS selection_margin_ms BIGINT;
@@ -0,0 +1,26 @@
+-- Migration 005: AI decision audit log
+-- One row per AiStrategy::select_blocks call: the grid it saw, the exact
+-- prompt and raw response, the parsed selection and latency. Outcome columns
+-- are filled in once the round's winning square is known.
+
+CREATE TABLE IF NOT EXISTS ai_decisions (
+    id UUID PRIMARY KEY,
+    model VARCHAR(128) NOT NULL,
+    round_id BIGINT NOT NULL,
+    grid JSONB NOT NULL,
+    prompt TEXT,
+    raw_response TEXT,
+    selection JSONB,
+    selected_blocks SMALLINT[] NOT NULL DEFAULT '{}',
+    skip BOOLEAN NOT NULL DEFAULT FALSE,
+    error TEXT,
+    latency_ms BIGINT NOT NULL,
+    winning_square SMALLINT,
+    won BOOLEAN,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_decisions_round ON ai_decisions(round_id);
+CREATE INDEX IF NOT EXISTS idx_ai_decisions_model_created ON ai_decisions(model, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_ai_decisions_unresolved ON ai_decisions(round_id) WHERE resolved_at IS NULL;
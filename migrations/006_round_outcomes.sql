@@ -0,0 +1,12 @@
+-- Migration 006: Round outcomes
+-- Winning square and final per-square stakes of finished rounds. Joined with
+-- ai_decisions grid snapshots, these are the training set for the local
+-- block-selection model (`train` subcommand).
+
+CREATE TABLE IF NOT EXISTS round_outcomes (
+    round_id BIGINT PRIMARY KEY,
+    winning_square SMALLINT NOT NULL,
+    final_deployed BIGINT[] NOT NULL,
+    final_miners BIGINT[] NOT NULL,
+    resolved_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,21 @@
+-- Migration 007: Strategy experiments
+-- Named sets of strategy arms assigned per wallet or by alternating rounds.
+-- Deploys are tagged with their experiment arm and selected squares so
+-- per-arm P&L can be computed once rounds settle.
+
+CREATE TABLE IF NOT EXISTS experiments (
+    id UUID PRIMARY KEY,
+    name VARCHAR(128) NOT NULL,
+    assignment JSONB NOT NULL,
+    arms JSONB NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'active',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ended_at TIMESTAMPTZ
+);
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS squares SMALLINT[];
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS experiment_id UUID REFERENCES experiments(id);
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS experiment_arm VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_experiment ON transactions(experiment_id, experiment_arm)
+    WHERE experiment_id IS NOT NULL;
@@ -0,0 +1,10 @@
+-- Migration 008: Session strategy scripts
+-- The current Rhai selection script of each session. Updating a script bumps
+-- its version; the mining loop picks the new one up at the next round.
+
+CREATE TABLE IF NOT EXISTS session_scripts (
+    session_id UUID PRIMARY KEY REFERENCES sessions(id),
+    source TEXT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
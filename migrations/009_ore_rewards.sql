@@ -0,0 +1,5 @@
+-- Migration 009: ORE rewards on settled deploys
+-- Pro-rata share of the round's ORE reward earned by a winning deploy
+-- (grains), so session P&L can value ORE alongside SOL.
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS ore_reward BIGINT;
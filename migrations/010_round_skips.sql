@@ -0,0 +1,16 @@
+-- Migration 010: Skipped rounds
+-- Every round a session sat out, with the reason and the motherlode at
+-- decision time (grains), so motherlode-hunting sessions can show which
+-- motherlode value kept them out.
+
+CREATE TABLE IF NOT EXISTS round_skips (
+    id UUID PRIMARY KEY,
+    session_id UUID NOT NULL REFERENCES sessions(id),
+    user_wallet VARCHAR(64) NOT NULL,
+    round_id BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    motherlode BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_round_skips_session ON round_skips(session_id, round_id);
@@ -0,0 +1,15 @@
+-- Migration 011: Risk controls
+-- Persistent risk state so restarts don't reset it. The `global` scope holds
+-- the kill switch and the point aggregate drawdown is measured from; wallet
+-- scopes hold loss-streak cooldowns (blocked until `until_round`).
+
+CREATE TABLE IF NOT EXISTS risk_state (
+    scope VARCHAR(64) PRIMARY KEY,
+    blocked BOOLEAN NOT NULL DEFAULT FALSE,
+    until_round BIGINT,
+    reason TEXT,
+    baseline_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_wallet_created ON transactions(user_wallet, created_at);
@@ -0,0 +1,21 @@
+-- Migration 012: Fleet sessions
+-- A fleet mines with several managed wallets as one session. Each wallet
+-- keeps its own sessions row (for per-wallet P&L), linked to the fleet.
+-- Wallet tags let a fleet name "all wallets tagged X".
+
+ALTER TABLE wallets ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+CREATE INDEX IF NOT EXISTS idx_wallets_tags ON wallets USING GIN (tags);
+
+CREATE TABLE IF NOT EXISTS fleets (
+    id UUID PRIMARY KEY,
+    lead_wallet VARCHAR(64) NOT NULL,
+    split VARCHAR(32) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ended_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_fleets_lead ON fleets(lead_wallet) WHERE is_active;
+
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS fleet_id UUID REFERENCES fleets(id);
+CREATE INDEX IF NOT EXISTS idx_sessions_fleet ON sessions(fleet_id);
@@ -0,0 +1,22 @@
+-- Migration 013: Session schedules
+-- Per-wallet schedules that start and stop sessions on their own: a time
+-- window or a cron expression (spec), the /api/session/start body each run
+-- uses (session), and limits on runs (max_rounds) and the schedule (until_at).
+
+CREATE TABLE IF NOT EXISTS schedules (
+    id UUID PRIMARY KEY,
+    wallet VARCHAR(64) NOT NULL,
+    spec JSONB NOT NULL,
+    session JSONB NOT NULL,
+    max_rounds BIGINT,
+    until_at TIMESTAMPTZ,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Session of the run in progress
+    session_id UUID REFERENCES sessions(id),
+    last_started_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedules_wallet ON schedules(wallet);
+CREATE INDEX IF NOT EXISTS idx_schedules_enabled ON schedules(enabled) WHERE enabled;
@@ -0,0 +1,14 @@
+-- Migration 014: Session change audit trail
+-- Every pause, resume and live config change made to a running session,
+-- with the fields changed (from/to, lamports).
+
+CREATE TABLE IF NOT EXISTS session_changes (
+    id UUID PRIMARY KEY,
+    session_id UUID NOT NULL REFERENCES sessions(id),
+    user_wallet VARCHAR(64) NOT NULL,
+    action VARCHAR(16) NOT NULL,
+    changes JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_changes_session ON session_changes(session_id, created_at);
@@ -0,0 +1,6 @@
+-- Migration 015: AI calls cut off by the decision deadline
+-- A call still running at the deadline is aborted before it can record
+-- itself, so it gets a row marked timed out, naming the answer used instead.
+
+ALTER TABLE ai_decisions ADD COLUMN IF NOT EXISTS timed_out BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE ai_decisions ADD COLUMN IF NOT EXISTS used_instead VARCHAR(16);
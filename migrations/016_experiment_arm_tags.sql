@@ -0,0 +1,10 @@
+-- Migration 016: Experiment arms on skips and AI decisions
+-- Skipped rounds and AI decisions carry the experiment arm that was active,
+-- like deploys do, so arms can be compared on what they sat out and on
+-- their AI calls as well as on settled deploys.
+
+ALTER TABLE round_skips ADD COLUMN IF NOT EXISTS experiment_id UUID REFERENCES experiments(id);
+ALTER TABLE round_skips ADD COLUMN IF NOT EXISTS experiment_arm VARCHAR(64);
+
+ALTER TABLE ai_decisions ADD COLUMN IF NOT EXISTS experiment_id UUID REFERENCES experiments(id);
+ALTER TABLE ai_decisions ADD COLUMN IF NOT EXISTS experiment_arm VARCHAR(64);